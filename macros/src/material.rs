@@ -35,6 +35,7 @@ pub(crate) fn derive_material_internal(input: DeriveInput) -> proc_macro2::Token
     let fields = data.fields.iter().map(get_builder_struct_field);
     let functions = data.fields.iter().map(get_builder_impl_function);
     let build_function = data.fields.iter().map(get_builder_impl_build);
+    let map_assets = get_map_assets(&data);
 
     quote! {
         #[::flatbox::assets::typetag::serde]
@@ -42,6 +43,7 @@ pub(crate) fn derive_material_internal(input: DeriveInput) -> proc_macro2::Token
             #vertex
             #fragment
             #input
+            #map_assets
         }
 
         impl #ident {
@@ -111,10 +113,8 @@ fn get_builder_struct_field(f: &Field) -> proc_macro2::TokenStream {
     }
 }
 
-fn get_builder_impl_function(f: &Field) -> proc_macro2::TokenStream {
-    let name = &f.ident;
-    let ty = &f.ty;
-    let attr = match f.attrs.get(0) {
+fn get_field_attribute(f: &Field) -> FieldAttribute {
+    match f.attrs.get(0) {
         Some(attr) => {
             if let Meta::Path(path) = &attr.meta {
                 match path.into_token_stream().to_string().as_str() {
@@ -127,9 +127,29 @@ fn get_builder_impl_function(f: &Field) -> proc_macro2::TokenStream {
             }
         },
         None => FieldAttribute::None,
-    };
+    }
+}
+
+fn get_map_assets(data: &DataStruct) -> proc_macro2::TokenStream {
+    let textures = data.fields
+        .iter()
+        .filter(|f| matches!(get_field_attribute(f), FieldAttribute::Texture))
+        .map(|f| &f.ident);
+
+    quote! {
+        fn map_assets(&mut self, mapping: &::flatbox::assets::AssetMapping) {
+            #(
+                self.#textures = mapping.map_texture_index(self.#textures);
+            )*
+        }
+    }
+}
+
+fn get_builder_impl_function(f: &Field) -> proc_macro2::TokenStream {
+    let name = &f.ident;
+    let ty = &f.ty;
 
-    match attr {
+    match get_field_attribute(f) {
         FieldAttribute::Color => quote! {
             pub fn #name(mut self, value: ::flatbox::render::Color<f32>) -> Self { 
                 self.#name = value.into();
@@ -1,16 +1,32 @@
+use std::any::TypeId;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
 use serde::{Serialize, Deserialize};
 
-#[cfg(feature = "render")]
-use std::sync::Arc;
 #[cfg(feature = "render")]
 use parking_lot::{RwLock, MappedRwLockReadGuard, MappedRwLockWriteGuard, RwLockReadGuard, RwLockWriteGuard};
 #[cfg(feature = "render")]
 use ash::vk;
 
-use crate::audio::AudioManager;
+use kira::sound::static_sound::StaticSoundData;
+
+use crate::audio::{AudioBackend, AudioManager, Sound};
+
+use crate::error::FlatboxResult;
 
+use super::{
+    AssetHandle,
+    loading::*,
+    loader::*,
+    storage::*,
+    hot_reload::*,
+    source::Vfs,
+    mapping::*,
+};
 #[cfg(feature = "render")]
-use super::AssetHandle;
+use super::meta::*;
 #[cfg(feature = "render")]
 use crate::render::*;
 
@@ -28,22 +44,33 @@ pub struct AssetManager {
     /// Game materials collection
     #[cfg(feature = "render")]
     pub materials: Vec<Arc<RwLock<Box<dyn Material>>>>,
+    /// Pool of workers, which load and decode assets in the background
+    #[serde(skip)]
+    pub loader: AssetLoadingPool,
+    /// Collections of custom assets, indexed by [`Asset::TYPE`]
+    #[serde(skip)]
+    custom_assets: HashMap<char, Box<dyn AnyAssets>>,
+    /// Custom asset loaders, indexed by file extension
+    #[serde(skip)]
+    loaders: HashMap<String, Arc<dyn ErasedAssetLoader>>,
+    /// Watcher of the asset files. Assets are reloaded on change, if it's set
+    #[serde(skip)]
+    pub watcher: Option<AssetWatcher>,
+    /// Assets, which are being reloaded in the background
+    #[serde(skip)]
+    reloading: HashSet<AssetId>,
+    /// Events, which are sent with the next [`update_loading`](#method.update_loading) call
+    #[serde(skip)]
+    pending_events: Vec<AssetEvent>,
+    /// Reloaded textures, which rendering data must be regenerated
+    #[cfg(feature = "render")]
+    #[serde(skip)]
+    pub(crate) outdated_textures: Vec<usize>,
 }
 
 impl Default for AssetManager {
     fn default() -> Self {
-        AssetManager {
-            audio: AudioManager::default(),
-            #[cfg(feature = "render")]
-            textures: vec![
-                Texture::new_solid(Color::<u8>::WHITE, TextureType::Plain, 16, 16),
-                Texture::new_solid(Color::<u8>::NORMAL, TextureType::Plain, 16, 16),
-            ],
-            #[cfg(feature = "render")]
-            skybox: None,
-            #[cfg(feature = "render")]
-            materials: vec![],
-        }
+        AssetManager::with_audio(AudioManager::default())
     }
 }
 
@@ -53,18 +80,39 @@ impl AssetManager {
         cast_count: usize, 
         listener_count: usize,
     ) -> Self {
+        AssetManager::with_audio_backend(cast_count, listener_count, AudioBackend::default())
+    }
+
+    /// Create new asset manager with audio settings and the audio `backend` specified
+    pub fn with_audio_backend(
+        cast_count: usize, 
+        listener_count: usize,
+        backend: AudioBackend,
+    ) -> Self {
+        AssetManager::with_audio(
+            AudioManager::with_backend(cast_count, listener_count, backend)
+                .expect("Cannot create audio manager")
+        )
+    }
+
+    /// Create new asset manager with the `audio` manager
+    pub fn with_audio(audio: AudioManager) -> Self {
         AssetManager {
-            audio: AudioManager::new(cast_count, listener_count)
-                .expect("Cannot create audio manager"),
+            audio,
             #[cfg(feature = "render")]
-            textures: vec![
-                Texture::new_solid(Color::<u8>::WHITE, TextureType::Plain, 16, 16),
-                Texture::new_solid(Color::<u8>::NORMAL, TextureType::Plain, 16, 16),
-            ],
+            textures: builtin_textures(),
             #[cfg(feature = "render")]
             skybox: None,
             #[cfg(feature = "render")]
             materials: vec![],
+            loader: AssetLoadingPool::new(),
+            custom_assets: HashMap::new(),
+            loaders: HashMap::new(),
+            watcher: None,
+            reloading: HashSet::new(),
+            pending_events: vec![],
+            #[cfg(feature = "render")]
+            outdated_textures: vec![],
         }
     }
 
@@ -88,25 +136,492 @@ impl AssetManager {
             self.textures.clear();
             self.materials.clear();
         }
+
+        for assets in self.custom_assets.values_mut() {
+            assets.clear();
+        }
+
+        self.loader.clear();
+        self.reloading.clear();
+        self.pending_events.clear();
+        #[cfg(feature = "render")]
+        self.outdated_textures.clear();
+    }
+
+    /// Move assets of the `other` manager into this one and return mapping of the `other`'s
+    /// handles to the merged ones. Builtin textures aren't duplicated
+    pub fn merge(&mut self, mut other: AssetManager) -> AssetMapping {
+        #[allow(unused_mut)]
+        let mut mapping = AssetMapping {
+            sound_offset: self.audio.sounds.len(),
+            playlist_offset: self.audio.playlists.len(),
+            ..Default::default()
+        };
+
+        #[cfg(feature = "render")]{
+            mapping.builtin_textures = other.textures.iter()
+                .zip(builtin_textures())
+                .take_while(|(texture, builtin)| texture.texture_load_type == builtin.texture_load_type)
+                .count();
+            mapping.texture_offset = self.textures.len();
+            mapping.material_offset = self.materials.len();
+
+            self.textures.extend(other.textures.drain(..).skip(mapping.builtin_textures));
+
+            for material in other.materials.drain(..) {
+                material.write().map_assets(&mapping);
+                self.materials.push(material);
+            }
+
+            if self.skybox.is_none() {
+                self.skybox = other.skybox.take();
+            }
+        }
+
+        self.audio.sounds.append(&mut other.audio.sounds);
+        self.audio.playlists.append(&mut other.audio.playlists);
+
+        for (asset_type, mut assets) in other.custom_assets.drain() {
+            match self.custom_assets.get_mut(&asset_type) {
+                Some(target) => {
+                    if let Some(offset) = target.merge(&mut *assets) {
+                        mapping.custom_offsets.insert(asset_type, offset);
+                    }
+                },
+                None => {
+                    self.custom_assets.insert(asset_type, assets);
+                },
+            }
+        }
+
+        mapping
+    }
+
+    /// Copy textures, materials and sounds into the new asset manager, e.g. to store them
+    /// in the [`Scene`](super::Scene). Asset handles stay valid for the copy. Audio of the
+    /// copy is silent, see [`AudioManager::snapshot`]
+    pub fn snapshot(&self) -> FlatboxResult<AssetManager> {
+        #[allow(unused_mut)]
+        let mut assets = AssetManager::with_audio(self.audio.snapshot()?);
+
+        #[cfg(feature = "render")]{
+            assets.textures = self.textures.clone();
+            assets.skybox = self.skybox.clone();
+
+            for material in &self.materials {
+                let data = ron::to_string(&*material.read())?;
+                let material: Box<dyn Material> = ron::from_str(&data)?;
+                assets.materials.push(Arc::new(RwLock::new(material)));
+            }
+        }
+
+        Ok(assets)
+    }
+
+    /// Register custom [`AssetLoader`]. Loaders, previously added for the same extensions, are replaced
+    pub fn add_loader<L: AssetLoader>(&mut self, loader: L) -> &mut Self {
+        self.add_asset_type::<L::Asset>();
+
+        let extensions = loader.extensions()
+            .iter()
+            .map(|ext| ext.to_lowercase())
+            .collect::<Vec<_>>();
+
+        let loader: Arc<dyn ErasedAssetLoader> = Arc::new(loader);
+        for extension in extensions {
+            self.loaders.insert(extension, loader.clone());
+        }
+
+        self
+    }
+
+    /// Register collection of custom assets of type `A`. Types, reserved by the engine, are rejected
+    pub fn add_asset_type<A: Asset>(&mut self) -> &mut Self {
+        match self.custom_assets.get(&A::TYPE) {
+            Some(assets) if (**assets).as_any().is::<Assets<A>>() => {},
+            Some(_) => {
+                log::error!("Asset type '{}' is already used by another asset than '{}'!", A::TYPE, std::any::type_name::<A>());
+            },
+            None if is_reserved::<A>() => {
+                log::error!("Asset type '{}' of '{}' is reserved by the engine!", A::TYPE, std::any::type_name::<A>());
+            },
+            None => {
+                self.custom_assets.insert(A::TYPE, Box::new(Assets::<A>::new()));
+            },
+        }
+
+        self
+    }
+
+    /// Get collection of custom assets of type `A`
+    pub fn assets<A: Asset>(&self) -> Option<&Assets<A>> {
+        (**self.custom_assets.get(&A::TYPE)?).as_any().downcast_ref::<Assets<A>>()
+    }
+
+    /// Mutably get collection of custom assets of type `A`
+    pub fn assets_mut<A: Asset>(&mut self) -> Option<&mut Assets<A>> {
+        (**self.custom_assets.get_mut(&A::TYPE)?).as_any_mut().downcast_mut::<Assets<A>>()
+    }
+
+    /// Add custom asset, which has been created manually
+    pub fn add_asset<A: Asset>(&mut self, asset: A) -> Option<Handle<A>> {
+        Some(self.add_asset_type::<A>().assets_mut::<A>()?.add(asset))
+    }
+
+    pub fn get<A: Asset>(&self, handle: Handle<A>) -> Option<&A> {
+        self.assets::<A>()?.get(handle)
+    }
+
+    pub fn get_mut<A: Asset>(&mut self, handle: Handle<A>) -> Option<&mut A> {
+        self.assets_mut::<A>()?.get_mut(handle)
+    }
+
+    /// Load custom asset in the background with the [`AssetLoader`], registered for the file extension.
+    /// If the asset has already been loaded from the same path, its handle is returned
+    pub fn load<A: Asset, P: AsRef<Path>>(&mut self, path: P) -> FlatboxResult<Handle<A>> {
+        let path = path.as_ref().to_owned();
+
+        if let Some(handle) = self.assets::<A>().and_then(|assets| assets.handle_of(&path)) {
+            return Ok(handle);
+        }
+
+        let loader = self.loader_for(&path)?;
+
+        if loader.asset_type_id() != TypeId::of::<A>() {
+            return Err(crate::Result::yell(format!(
+                "Cannot load asset '{}': its loader doesn't produce '{}'",
+                path.display(),
+                std::any::type_name::<A>(),
+            )));
+        }
+
+        let handle = self.add_asset_type::<A>()
+            .assets_mut::<A>()
+            .ok_or(crate::Result::yell(format!("Cannot register asset type '{}'", std::any::type_name::<A>())))?
+            .reserve(path.clone());
+
+        self.loader.spawn_erased(handle.id(), move || {
+            let bytes = Vfs::read(&path)
+                .map_err(|e| format!("Cannot read asset '{}': {e}", path.display()))?;
+
+            loader.load_erased(&bytes, &path)
+        });
+
+        Ok(handle)
+    }
+
+    /// Find loader, registered for the file extension
+    fn loader_for(&self, path: &Path) -> FlatboxResult<Arc<dyn ErasedAssetLoader>> {
+        let extension = path.extension()
+            .and_then(|ext| ext.to_str())
+            .map(|ext| ext.to_lowercase())
+            .ok_or(crate::Result::yell(format!("Cannot load asset '{}': invalid file extension", path.display())))?;
+
+        self.loaders.get(&extension)
+            .cloned()
+            .ok_or(crate::Result::yell(format!("Cannot load asset '{}': no loader for '.{extension}' files", path.display())))
+    }
+
+    /// Load sound in the background. The sound can be played, when its [`LoadState`] is `Loaded`
+    pub fn load_sound(
+        &mut self,
+        path: impl Into<String>,
+    ) -> AssetHandle<'S'> {
+        let path = path.into();
+        let handle = self.audio.push_empty_sound(path.clone());
+
+        self.loader.spawn(handle.id(), move || {
+            Sound::decode_file(&path)
+                .map_err(|e| format!("Cannot load sound '{path}': {e}"))
+        });
+
+        handle
+    }
+
+    /// Get loading state of the asset. Assets, which were created synchronously, are always `Loaded`
+    pub fn load_state(&self, id: impl Into<AssetId>) -> LoadState {
+        let id = id.into();
+
+        if let Some(state) = self.loader.state(id) {
+            return state.clone();
+        }
+
+        if self.contains(id) {
+            LoadState::Loaded
+        } else {
+            LoadState::NotLoaded
+        }
+    }
+
+    /// Check whether all assets, requested to load in the background, are loaded (or failed)
+    pub fn is_loaded(&self) -> bool {
+        !self.loader.is_loading()
+    }
+
+    /// Reload asset from its file in the background. Handle of the asset stays valid and
+    /// the previous data is used until the new one is loaded. Returns `false` if the asset
+    /// isn't loaded from file
+    pub fn reload(&mut self, id: impl Into<AssetId>) -> bool {
+        let id = id.into();
+
+        if self.loader.state(id) == Some(&LoadState::Loading) {
+            return false;
+        }
+
+        match id.asset_type {
+            #[cfg(feature = "render")]
+            'T' => {
+                let path = match self.textures.get(id.index).map(|t| &t.texture_load_type) {
+                    Some(TextureLoadType::Loaded(path)) => path.clone(),
+                    _ => return false,
+                };
+
+                self.loader.spawn(id, move || Texture::decode_file(&path));
+            },
+            'S' => {
+                // Streamed sounds are read from the file on every playback
+                let path = match self.audio.get_sound(AssetHandle::from_index(id.index)) {
+                    Some(sound) if !sound.is_streaming() => match sound.path() {
+                        Some(path) => path.to_path_buf(),
+                        None => return false,
+                    },
+                    _ => return false,
+                };
+
+                self.loader.spawn(id, move || {
+                    Sound::decode_file(&path)
+                        .map_err(|e| format!("Cannot load sound '{}': {e}", path.display()))
+                });
+            },
+            asset_type => {
+                let path = match self.custom_assets.get(&asset_type) {
+                    Some(assets) => assets.paths().into_iter().find(|(_, index)| *index == id.index),
+                    None => None,
+                };
+
+                let (path, loader) = match path.map(|(path, _)| (self.loader_for(&path), path)) {
+                    Some((Ok(loader), path)) => (path, loader),
+                    _ => return false,
+                };
+
+                self.loader.spawn_erased(id, move || {
+                    let bytes = Vfs::read(&path)
+                        .map_err(|e| format!("Cannot read asset '{}': {e}", path.display()))?;
+
+                    loader.load_erased(&bytes, &path)
+                });
+            },
+        }
+
+        self.reloading.insert(id);
+        true
+    }
+
+    /// Reload textures, decoded sounds and custom assets, which files have been changed since
+    /// the last check. Changes of the files, added with [`AssetWatcher::watch`], are reported
+    /// with [`AssetEvent::FileModified`], and scenes are respawned by the
+    /// [`hot_reload_scenes`](crate::ecs::hot_reload_scenes) system. Does nothing if [`watcher`](#structfield.watcher)
+    /// isn't set. Returns `false` if the watcher's poll interval hasn't elapsed yet
+    pub fn reload_modified(&mut self) -> bool {
+        let mut watcher = match self.watcher.take() {
+            Some(watcher) => watcher,
+            None => return false,
+        };
+
+        if !watcher.should_poll() {
+            self.watcher = Some(watcher);
+            return false;
+        }
+
+        let mut files: Vec<(AssetId, PathBuf)> = vec![];
+
+        #[cfg(feature = "render")]
+        for (index, texture) in self.textures.iter().enumerate() {
+            if let TextureLoadType::Loaded(path) = &texture.texture_load_type {
+                files.push((AssetId::new('T', index), path.clone()));
+            }
+        }
+
+        // Streamed sounds are read from the file on every playback
+        for (index, sound) in self.audio.sounds.iter().enumerate().filter(|(_, sound)| !sound.is_streaming()) {
+            if let Some(path) = sound.path() {
+                files.push((AssetId::new('S', index), path.to_path_buf()));
+            }
+        }
+
+        for (asset_type, assets) in &self.custom_assets {
+            for (path, index) in assets.paths() {
+                files.push((AssetId::new(*asset_type, index), path));
+            }
+        }
+
+        // Several assets may be loaded from the same file, so changes are checked first
+        let changed = files.iter()
+            .filter_map(|(_, path)| Some((path.clone(), watcher.changed(path)?)))
+            .collect::<HashMap<_, _>>();
+
+        let mut accepted = changed.clone();
+
+        for (id, path) in files {
+            if changed.contains_key(&path) {
+                log::info!("Asset '{}' is changed, reloading", path.display());
+
+                // Assets, which are still loading, are reloaded on the next poll
+                if !self.reload(id) {
+                    accepted.remove(&path);
+                }
+            }
+        }
+
+        for (path, time) in accepted {
+            watcher.accept(&path, time);
+        }
+
+        for path in watcher.watched().to_vec() {
+            if watcher.is_modified(&path) {
+                watcher.file_modified(&path);
+                self.pending_events.push(AssetEvent::FileModified(path));
+            }
+        }
+
+        self.watcher = Some(watcher);
+        true
+    }
+
+    /// Queue event, which is sent with the next [`update_loading`](#method.update_loading) call
+    pub(crate) fn push_event(&mut self, event: AssetEvent) {
+        self.pending_events.push(event);
+    }
+
+    /// Apply assets, which have been decoded in the background since the last call, and
+    /// return loading events. It's called by the [`update_assets`](crate::ecs::update_assets) system
+    pub fn update_loading(&mut self) -> Vec<AssetEvent> {
+        let mut events = std::mem::take(&mut self.pending_events);
+
+        for (id, result) in self.loader.poll() {
+            let reloaded = self.reloading.remove(&id);
+
+            if let (true, Err(error)) = (reloaded, &result) {
+                log::error!("{error}");
+                self.loader.set_state(id, LoadState::Loaded);
+                events.push(AssetEvent::Failed(id, error.clone()));
+                continue;
+            }
+
+            match self.apply_loaded(id, result) {
+                Ok(()) if reloaded => {
+                    self.loader.set_state(id, LoadState::Loaded);
+                    events.push(AssetEvent::Modified(id));
+                },
+                Ok(()) => {
+                    self.loader.set_state(id, LoadState::Loaded);
+                    events.push(AssetEvent::Loaded(id));
+                },
+                Err(error) => {
+                    log::error!("{error}");
+                    self.loader.set_state(id, LoadState::Failed(error.clone()));
+                    events.push(AssetEvent::Failed(id, error));
+                },
+            }
+        }
+
+        events
+    }
+
+    fn apply_loaded(
+        &mut self, 
+        id: AssetId, 
+        result: Result<LoadedAsset, String>,
+    ) -> Result<(), String> {
+        match id.asset_type {
+            #[cfg(feature = "render")]
+            'T' => {
+                let texture = self.textures.get_mut(id.index)
+                    .ok_or(format!("Loaded texture {id:?} doesn't exist"))?;
+
+                match result.and_then(|data| data.downcast::<image::RgbaImage>().map_err(|_| String::from("Invalid texture data"))) {
+                    Ok(image) => {
+                        texture.image = Some(*image);
+                        if texture.vk_image.is_some() {
+                            self.outdated_textures.push(id.index);
+                        }
+                        Ok(())
+                    },
+                    Err(error) => {
+                        texture.image = Some(Texture::no_image().image.unwrap());
+                        Err(error)
+                    },
+                }
+            },
+            'S' => {
+                let data = result?
+                    .downcast::<StaticSoundData>()
+                    .map_err(|_| String::from("Invalid sound data"))?;
+
+                let sound = self.audio.get_sound_mut(AssetHandle::from_index(id.index))
+                    .ok_or(format!("Loaded sound {id:?} doesn't exist"))?;
+
+                sound.set_data(*data);
+                Ok(())
+            },
+            asset_type => {
+                self.custom_assets.get_mut(&asset_type)
+                    .ok_or(format!("Cannot apply loaded asset {id:?}: unknown asset type"))?
+                    .set_loaded(id.index, result?)
+            },
+        }
+    }
+
+    fn contains(&self, id: AssetId) -> bool {
+        match id.asset_type {
+            #[cfg(feature = "render")]
+            'T' => id.index < self.textures.len(),
+            #[cfg(feature = "render")]
+            'M' => id.index < self.materials.len(),
+            'S' => id.index < self.audio.sounds.len(),
+            asset_type => self.custom_assets
+                .get(&asset_type)
+                .is_some_and(|assets| assets.contains(id.index)),
+        }
     }
 }
 
 #[cfg(feature = "render")]
 impl AssetManager {
+    /// Load texture with the import settings of its [`TextureMeta`] sidecar
+    pub fn load_texture(&mut self, path: impl Into<String>) -> AssetHandle<'T'> {
+        let path = path.into();
+        let meta = TextureMeta::load_or_default(&path);
+
+        self.create_texture_from_meta(path, &meta)
+    }
+
+    /// Load texture with the `filter`, which overrides the one of its [`TextureMeta`] sidecar.
+    /// Use [`load_texture`](#method.load_texture) to keep the filter of the sidecar
     pub fn create_texture(
         &mut self,
         path: impl Into<String>,
         filter: Filter,
     ) -> AssetHandle<'T'> {
-        let new_texture = Texture::new_from_path(
-            &path.into(),
+        let path = path.into();
+        let meta = TextureMeta {
             filter,
-            TextureType::Plain,
-        );
+            ..TextureMeta::load_or_default(&path)
+        };
+
+        self.create_texture_from_meta(path, &meta)
+    }
+
+    fn create_texture_from_meta(&mut self, path: String, meta: &TextureMeta) -> AssetHandle<'T'> {
+        let new_texture = Texture::new_from_meta(&path, meta);
         
         let new_id = self.textures.len();
         self.textures.push(new_texture);
-        AssetHandle(new_id)
+
+        let handle = AssetHandle(new_id);
+        self.loader.spawn(handle.id(), move || Texture::decode_file(&path));
+
+        handle
     }
 
     pub fn create_solid_texture(
@@ -197,23 +712,49 @@ impl AssetManager {
         None
     }
     
+    /// Image infos of all textures, where the index of the info is the index of the texture handle.
+    /// Textures, which aren't generated yet, are replaced with the builtin white texture.
+    /// Returns nothing until the builtin textures are generated
     pub fn descriptor_image_info(&self) -> Vec<vk::DescriptorImageInfo> {
+        let image_info = |texture: &Texture| match (texture.imageview, texture.sampler) {
+            (Some(image_view), Some(sampler)) => Some(
+                vk::DescriptorImageInfo {
+                    image_layout: vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL,
+                    image_view,
+                    sampler,
+                    ..Default::default()
+                }
+            ),
+            _ => None,
+        };
+
+        let placeholder = match self.textures.first().and_then(image_info) {
+            Some(placeholder) => placeholder,
+            None => return vec![],
+        };
+
         self.textures
             .iter()
-            .filter_map(|t| {
-                if let (Some(image_view), Some(sampler)) = (t.imageview, t.sampler) {
-                    Some(
-                        vk::DescriptorImageInfo {
-                            image_layout: vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL,
-                            image_view,
-                            sampler,
-                            ..Default::default()
-                        }
-                    )
-                } else {
-                    None
-                }
-            })
+            .map(|t| image_info(t).unwrap_or(placeholder))
             .collect()
     }
 }
+
+/// Textures, which every asset manager starts with
+#[cfg(feature = "render")]
+fn builtin_textures() -> Vec<Texture> {
+    vec![
+        Texture::new_solid(Color::<u8>::WHITE, TextureType::Plain, 16, 16),
+        Texture::new_solid(Color::<u8>::NORMAL, TextureType::Plain, 16, 16),
+    ]
+}
+
+/// Check if the asset type `A` uses type character of the engine assets:
+/// textures (`'T'`), materials (`'M'`), sounds (`'S'`), playlists (`'L'`) and [`Prefab`](super::Prefab)s (`'P'`)
+fn is_reserved<A: Asset>() -> bool {
+    match A::TYPE {
+        'T' | 'M' | 'S' | 'L' => true,
+        'P' => TypeId::of::<A>() != TypeId::of::<super::prefab::Prefab>(),
+        _ => false,
+    }
+}
@@ -0,0 +1,135 @@
+use std::path::Path;
+
+use serde::{Serialize, de::DeserializeOwned};
+use ron::ser::PrettyConfig;
+
+use crate::error::FlatboxResult;
+
+use super::source::Vfs;
+
+/// Header of the files in the [`SerialFormat::Binary`] format
+pub const BINARY_MAGIC: &[u8; 8] = b"FLATBIN1";
+
+/// Format of the serialized scenes and save archive entries. Format of the
+/// loaded data is detected by its header, so both of them can be mixed
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum SerialFormat {
+    /// Human-readable pretty-printed RON. Use it for authoring
+    #[default]
+    Ron,
+    /// Compact MessagePack with the [`BINARY_MAGIC`] header. Use it for shipping
+    Binary,
+}
+
+impl SerialFormat {
+    /// Detect format of the serialized data
+    pub fn detect(data: &[u8]) -> Self {
+        if data.starts_with(BINARY_MAGIC) {
+            SerialFormat::Binary
+        } else {
+            SerialFormat::Ron
+        }
+    }
+
+    pub fn serialize<T: Serialize + ?Sized>(&self, value: &T) -> FlatboxResult<Vec<u8>> {
+        match self {
+            SerialFormat::Ron => {
+                let data = ron::ser::to_string_pretty(value, PrettyConfig::new().struct_names(true))?;
+                Ok(data.into_bytes())
+            },
+            SerialFormat::Binary => {
+                let mut data = BINARY_MAGIC.to_vec();
+                value.serialize(&mut binary_serializer(&mut data))?;
+                Ok(data)
+            },
+        }
+    }
+
+    /// Deserialize data of any format
+    pub fn deserialize<T: DeserializeOwned>(data: &[u8]) -> FlatboxResult<T> {
+        match SerialFormat::detect(data) {
+            SerialFormat::Ron => Ok(ron::de::from_bytes(data)?),
+            SerialFormat::Binary => Ok(rmp_serde::from_slice(&data[BINARY_MAGIC.len()..])?),
+        }
+    }
+
+    /// Read file of any format
+    pub fn load<T: DeserializeOwned, P: AsRef<Path>>(path: P) -> FlatboxResult<T> {
+        SerialFormat::deserialize(&Vfs::read(path)?)
+    }
+
+    pub fn save<T: Serialize + ?Sized, P: AsRef<Path>>(&self, value: &T, path: P) -> FlatboxResult<()> {
+        std::fs::write(path, self.serialize(value)?)?;
+        Ok(())
+    }
+
+    /// Convert file of type `T` to the format, e.g. RON scene to the binary one for shipping
+    pub fn convert<T, P, Q>(&self, input: P, output: Q) -> FlatboxResult<()>
+    where
+        T: Serialize + DeserializeOwned,
+        P: AsRef<Path>,
+        Q: AsRef<Path>,
+    {
+        let value: T = SerialFormat::load(input)?;
+        self.save(&value, output)
+    }
+}
+
+/// MessagePack serializer, which is used by [`SerialFormat::Binary`]. Structs are written
+/// as maps, since tagged components and materials can't be read from arrays
+pub fn binary_serializer<W: std::io::Write>(writer: W) -> rmp_serde::Serializer<W, rmp_serde::config::StructMapConfig<rmp_serde::config::DefaultConfig>> {
+    rmp_serde::Serializer::new(writer).with_struct_map()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use super::*;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Entry {
+        name: String,
+        values: Vec<u32>,
+        nested: Option<Box<Entry>>,
+    }
+
+    fn entry() -> Entry {
+        Entry {
+            name: String::from("root"),
+            values: vec![1, 2, 3],
+            nested: Some(Box::new(Entry {
+                name: String::from("child"),
+                values: vec![],
+                nested: None,
+            })),
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        for format in [SerialFormat::Ron, SerialFormat::Binary] {
+            let data = format.serialize(&entry()).unwrap();
+
+            assert_eq!(SerialFormat::detect(&data), format);
+            assert_eq!(SerialFormat::deserialize::<Entry>(&data).unwrap(), entry());
+        }
+    }
+
+    #[test]
+    fn binary_has_magic() {
+        let data = SerialFormat::Binary.serialize(&entry()).unwrap();
+        assert!(data.starts_with(BINARY_MAGIC));
+
+        let data = SerialFormat::Ron.serialize(&entry()).unwrap();
+        assert!(!data.starts_with(BINARY_MAGIC));
+    }
+
+    #[test]
+    fn invalid_binary_data() {
+        let mut data = BINARY_MAGIC.to_vec();
+        data.push(0xc1);
+
+        assert!(SerialFormat::deserialize::<Entry>(&data).is_err());
+    }
+}
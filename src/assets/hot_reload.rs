@@ -0,0 +1,183 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::{Flatbox, Extension};
+use crate::ecs::{Entity, hot_reload_assets, hot_reload_prefabs, hot_reload_scenes};
+
+use super::source::Vfs;
+
+/// Default interval between file modification checks
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Watcher, which polls modification times of the loaded asset files.
+/// It's stored in the [`AssetManager`](super::AssetManager) and enabled with [`HotReload`] extension
+#[derive(Debug, Clone)]
+pub struct AssetWatcher {
+    /// Interval between file modification checks
+    pub interval: Duration,
+    last_poll: Option<Instant>,
+    modified: HashMap<PathBuf, SystemTime>,
+    watched: Vec<PathBuf>,
+    /// Spawned instances of the watched scenes
+    scenes: HashMap<PathBuf, Vec<Vec<Entity>>>,
+    outdated_scenes: Vec<PathBuf>,
+}
+
+impl AssetWatcher {
+    pub fn new(interval: Duration) -> Self {
+        AssetWatcher {
+            interval,
+            last_poll: None,
+            modified: HashMap::new(),
+            watched: vec![],
+            scenes: HashMap::new(),
+            outdated_scenes: vec![],
+        }
+    }
+
+    /// Watch additional file, which is not managed by the engine (e.g. scene).
+    /// When the file is changed, [`AssetEvent::FileModified`](super::AssetEvent::FileModified) is sent
+    pub fn watch<P: Into<PathBuf>>(&mut self, path: P) {
+        let path = path.into();
+
+        if !self.watched.contains(&path) {
+            self.is_modified(&path);
+            self.watched.push(path);
+        }
+    }
+
+    /// Watch scene file, which `entities` have been spawned from, e.g. with
+    /// [`spawn_scene_additive`](super::SpawnSceneExt::spawn_scene_additive). When the file
+    /// is changed, the entities are despawned and the scene is spawned again in their place
+    pub fn watch_scene<P: Into<PathBuf>>(&mut self, path: P, entities: Vec<Entity>) {
+        let path = path.into();
+
+        self.watch(path.clone());
+        self.scenes.entry(path).or_default().push(entities);
+    }
+
+    /// Stop watching file, added with [`watch`](#method.watch) or [`watch_scene`](#method.watch_scene)
+    pub fn unwatch<P: AsRef<Path>>(&mut self, path: P) {
+        self.watched.retain(|p| p != path.as_ref());
+        self.modified.remove(path.as_ref());
+        self.scenes.remove(path.as_ref());
+    }
+
+    /// Files, added with [`watch`](#method.watch)
+    pub fn watched(&self) -> &[PathBuf] {
+        &self.watched
+    }
+
+    /// Check whether the file has been modified since the last check.
+    /// The first check of the file only remembers its modification time
+    pub fn is_modified<P: AsRef<Path>>(&mut self, path: P) -> bool {
+        let path = path.as_ref();
+
+        let time = match Vfs::modified(path) {
+            Some(time) => time,
+            None => return false,
+        };
+
+        match self.modified.insert(path.to_owned(), time) {
+            Some(last) => last != time,
+            None => false,
+        }
+    }
+
+    /// New modification time of the file, if it has been changed since the last accepted one.
+    /// Unlike [`is_modified`](#method.is_modified), the time isn't remembered until it's
+    /// [`accept`](#method.accept)ed, so the change is reported again on the next poll
+    pub(crate) fn changed(&mut self, path: &Path) -> Option<SystemTime> {
+        let time = Vfs::modified(path)?;
+
+        match self.modified.get(path) {
+            Some(last) if *last != time => Some(time),
+            Some(_) => None,
+            None => {
+                self.modified.insert(path.to_owned(), time);
+                None
+            },
+        }
+    }
+
+    /// Mark the watched file as changed. Instances of the scene are respawned by the
+    /// [`hot_reload_scenes`](crate::ecs::hot_reload_scenes) system
+    pub(crate) fn file_modified(&mut self, path: &Path) {
+        if self.scenes.contains_key(path) && !self.outdated_scenes.iter().any(|p| p == path) {
+            self.outdated_scenes.push(path.to_owned());
+        }
+    }
+
+    /// Take the changed scenes along with their spawned instances. Respawned instances
+    /// are watched again with [`watch_scene`](#method.watch_scene)
+    pub(crate) fn take_outdated_scenes(&mut self) -> Vec<(PathBuf, Vec<Vec<Entity>>)> {
+        std::mem::take(&mut self.outdated_scenes)
+            .into_iter()
+            .filter_map(|path| {
+                let instances = self.scenes.remove(&path)?;
+                Some((path, instances))
+            })
+            .collect()
+    }
+
+    /// Remember modification time of the file, which change has been handled
+    pub(crate) fn accept(&mut self, path: &Path, time: SystemTime) {
+        self.modified.insert(path.to_owned(), time);
+    }
+
+    /// Check whether the poll interval has elapsed since the last poll
+    pub(crate) fn should_poll(&mut self) -> bool {
+        let now = Instant::now();
+
+        match self.last_poll {
+            Some(last) if now.duration_since(last) < self.interval => false,
+            _ => {
+                self.last_poll = Some(now);
+                true
+            },
+        }
+    }
+}
+
+impl Default for AssetWatcher {
+    fn default() -> Self {
+        AssetWatcher::new(DEFAULT_POLL_INTERVAL)
+    }
+}
+
+/// Extension, which enables reloading of the changed asset files in place. Textures, decoded
+/// sounds, custom assets, models and Lua scripts are reloaded automatically. Streamed sounds
+/// are read from the file on every playback, so they don't need reloading. Scenes, added with
+/// [`AssetWatcher::watch_scene`], are respawned, while other files, added with
+/// [`AssetWatcher::watch`], are only reported with events. Changes of the [`Prefab`](super::Prefab)s
+/// and their bases are re-applied to the spawned instances. Materials are created in code
+/// rather than loaded from files, so they aren't reloaded
+///
+/// # Usage example
+///
+/// ```rust
+/// Flatbox::init(WindowBuilder::default())
+///     .default_systems()
+///     .apply_extension(HotReload::default())
+///     .run();
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct HotReload {
+    pub interval: Duration,
+}
+
+impl Default for HotReload {
+    fn default() -> Self {
+        HotReload { interval: DEFAULT_POLL_INTERVAL }
+    }
+}
+
+impl Extension for HotReload {
+    fn apply(&self, app: &mut Flatbox) {
+        app.asset_manager.watcher = Some(AssetWatcher::new(self.interval));
+        app.add_system(hot_reload_assets);
+        app.add_system(hot_reload_scenes);
+        app.add_system(hot_reload_prefabs);
+    }
+}
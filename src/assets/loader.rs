@@ -0,0 +1,76 @@
+use std::any::TypeId;
+use std::path::Path;
+
+use crate::error::FlatboxResult;
+
+use super::{
+    storage::Asset,
+    loading::LoadedAsset,
+};
+
+/// Trait for loading custom [`Asset`]s from files with concrete extensions.
+/// Loaders are registered with `AssetManager::add_loader` and run on the worker threads
+///
+/// # Usage example
+///
+/// ```rust
+/// #[derive(Deserialize)]
+/// struct Dialogue {
+///     lines: Vec<String>,
+/// }
+///
+/// impl Asset for Dialogue {
+///     const TYPE: char = 'D';
+/// }
+///
+/// struct DialogueLoader;
+///
+/// impl AssetLoader for DialogueLoader {
+///     type Asset = Dialogue;
+///
+///     fn extensions(&self) -> &[&str] {
+///         &["dialogue"]
+///     }
+///
+///     fn load(&self, bytes: &[u8], _path: &Path) -> FlatboxResult<Dialogue> {
+///         Ok(ron::de::from_bytes(bytes)?)
+///     }
+/// }
+///
+/// asset_manager.add_loader(DialogueLoader);
+/// let dialogue: Handle<Dialogue> = asset_manager.load("assets/intro.dialogue")?;
+/// ```
+pub trait AssetLoader: Send + Sync + 'static {
+    type Asset: Asset;
+
+    /// File extensions (without leading dot), which are handled by the loader
+    fn extensions(&self) -> &[&str];
+
+    /// Decode asset from the file content
+    fn load(&self, bytes: &[u8], path: &Path) -> FlatboxResult<Self::Asset>;
+}
+
+/// Type-erased [`AssetLoader`], which is shared between worker threads
+pub(crate) trait ErasedAssetLoader: Send + Sync {
+    fn asset_type_id(&self) -> TypeId;
+
+    fn load_erased(&self, bytes: &[u8], path: &Path) -> Result<LoadedAsset, String>;
+}
+
+impl<L: AssetLoader> ErasedAssetLoader for L {
+    fn asset_type_id(&self) -> TypeId {
+        TypeId::of::<L::Asset>()
+    }
+
+    fn load_erased(&self, bytes: &[u8], path: &Path) -> Result<LoadedAsset, String> {
+        self.load(bytes, path)
+            .map(|asset| Box::new(asset) as LoadedAsset)
+            .map_err(|e| format!("Cannot load asset '{}': {e}", path.display()))
+    }
+}
+
+impl std::fmt::Debug for dyn ErasedAssetLoader {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("AssetLoader")
+    }
+}
@@ -0,0 +1,188 @@
+use std::any::Any;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::mpsc::{channel, Sender, Receiver};
+use std::thread::{self, JoinHandle};
+
+use parking_lot::Mutex;
+use serde::{Serialize, Deserialize};
+
+/// Asset data, decoded on the worker thread and not yet applied to the [`AssetManager`](super::AssetManager)
+pub type LoadedAsset = Box<dyn Any + Send>;
+
+type LoadJob = Box<dyn FnOnce() -> Result<LoadedAsset, String> + Send>;
+type LoadResult = (AssetId, Result<LoadedAsset, String>);
+
+/// Loading state of the asset with concrete handle
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum LoadState {
+    /// Asset hasn't been requested to load
+    #[default]
+    NotLoaded,
+    /// Asset is being decoded in the background
+    Loading,
+    /// Asset is decoded and ready to use
+    Loaded,
+    /// Asset loading failed with the following error
+    Failed(String),
+}
+
+/// Untyped asset identifier: type of the asset (e.g. `'T'` for textures) and its index
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AssetId {
+    pub asset_type: char,
+    pub index: usize,
+}
+
+impl AssetId {
+    pub fn new(asset_type: char, index: usize) -> Self {
+        AssetId { asset_type, index }
+    }
+}
+
+/// Event, which is sent when background asset loading is finished. Can be read
+/// with `Events::get_handler::<AssetEvent>()`
+#[derive(Clone, Debug, PartialEq)]
+pub enum AssetEvent {
+    /// Asset is loaded and ready to use
+    Loaded(AssetId),
+    /// Asset loading failed
+    Failed(AssetId, String),
+    /// Asset file has been changed and the asset is reloaded in place
+    Modified(AssetId),
+    /// File, which isn't stored in the [`AssetManager`](super::AssetManager) (e.g. model,
+    /// script or watched scene), has been changed
+    FileModified(PathBuf),
+}
+
+/// Pool of worker threads, which load and decode assets in the background.
+/// Workers are spawned lazily, when the first asset is requested to load
+#[derive(Default)]
+pub struct AssetLoadingPool {
+    sender: Option<Sender<(AssetId, LoadJob)>>,
+    receiver: Option<Mutex<Receiver<LoadResult>>>,
+    workers: Vec<JoinHandle<()>>,
+    states: HashMap<AssetId, LoadState>,
+}
+
+impl AssetLoadingPool {
+    pub fn new() -> Self {
+        AssetLoadingPool::default()
+    }
+
+    /// Send loading job to the workers and mark asset as [`LoadState::Loading`]
+    pub fn spawn<T, F>(&mut self, id: AssetId, job: F)
+    where
+        T: Send + 'static,
+        F: FnOnce() -> Result<T, String> + Send + 'static,
+    {
+        self.spawn_erased(id, move || job().map(|data| Box::new(data) as LoadedAsset));
+    }
+
+    /// Send loading job, which produces type-erased asset data, to the workers
+    pub fn spawn_erased<F>(&mut self, id: AssetId, job: F)
+    where
+        F: FnOnce() -> Result<LoadedAsset, String> + Send + 'static,
+    {
+        if self.sender.is_none() {
+            self.spawn_workers();
+        }
+
+        let job: LoadJob = Box::new(job);
+
+        match self.sender.as_ref().unwrap().send((id, job)) {
+            Ok(_) => { self.states.insert(id, LoadState::Loading); },
+            Err(_) => { self.states.insert(id, LoadState::Failed(String::from("Asset loading workers are stopped"))); },
+        }
+    }
+
+    /// Get loading state of the asset, if it's loaded in the background
+    pub fn state(&self, id: AssetId) -> Option<&LoadState> {
+        self.states.get(&id)
+    }
+
+    pub fn set_state(&mut self, id: AssetId, state: LoadState) {
+        self.states.insert(id, state);
+    }
+
+    /// Check whether any asset is still being loaded
+    pub fn is_loading(&self) -> bool {
+        self.pending() > 0
+    }
+
+    /// Count of assets, which are still being loaded
+    pub fn pending(&self) -> usize {
+        self.states
+            .values()
+            .filter(|s| **s == LoadState::Loading)
+            .count()
+    }
+
+    /// Collect assets, which have been decoded since the last call
+    pub(crate) fn poll(&mut self) -> Vec<LoadResult> {
+        match &self.receiver {
+            Some(receiver) => receiver.lock().try_iter().collect(),
+            None => vec![],
+        }
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.states.clear();
+    }
+
+    fn spawn_workers(&mut self) {
+        let (job_sender, job_receiver) = channel::<(AssetId, LoadJob)>();
+        let (result_sender, result_receiver) = channel::<LoadResult>();
+        let job_receiver = Arc::new(Mutex::new(job_receiver));
+
+        let count = thread::available_parallelism()
+            .map(|n| n.get().saturating_sub(1))
+            .unwrap_or(1)
+            .max(1);
+
+        for i in 0..count {
+            let job_receiver = job_receiver.clone();
+            let result_sender = result_sender.clone();
+
+            let worker = thread::Builder::new()
+                .name(format!("flatbox-asset-loader-{i}"))
+                .spawn(move || loop {
+                    let job = job_receiver.lock().recv();
+                    match job {
+                        Ok((id, job)) => {
+                            if result_sender.send((id, job())).is_err() {
+                                break;
+                            }
+                        },
+                        Err(_) => break,
+                    }
+                })
+                .expect("Cannot spawn asset loading worker");
+
+            self.workers.push(worker);
+        }
+
+        self.sender = Some(job_sender);
+        self.receiver = Some(Mutex::new(result_receiver));
+    }
+}
+
+impl Drop for AssetLoadingPool {
+    fn drop(&mut self) {
+        self.sender = None;
+
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
+
+impl std::fmt::Debug for AssetLoadingPool {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AssetLoadingPool")
+         .field("workers", &self.workers.len())
+         .field("states", &self.states)
+         .finish()
+    }
+}
@@ -0,0 +1,108 @@
+use std::collections::HashMap;
+
+use super::{AssetHandle, storage::{Asset, Handle}};
+
+/// Mapping of the asset handles, which is produced when one [`AssetManager`](super::AssetManager)
+/// is merged into another (e.g. during additive scene spawning)
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AssetMapping {
+    /// Count of the builtin textures of the merged manager, which aren't copied
+    pub builtin_textures: usize,
+    /// Index of the first merged non-builtin texture
+    pub texture_offset: usize,
+    /// Index of the first merged material
+    pub material_offset: usize,
+    /// Index of the first merged sound
+    pub sound_offset: usize,
+    /// Index of the first merged playlist
+    pub playlist_offset: usize,
+    /// Index of the first merged asset of every custom [`Asset`] type. Handles of the
+    /// types, which the target manager didn't store before merging, are left as is
+    pub custom_offsets: HashMap<char, usize>,
+}
+
+impl AssetMapping {
+    /// Map handle of the merged asset manager to the target one. Invalid handles and
+    /// handles of builtin textures are left as is
+    pub fn map<const TYPE: char>(&self, handle: AssetHandle<TYPE>) -> AssetHandle<TYPE> {
+        AssetHandle::from_index(self.map_index(TYPE, handle.unwrap()))
+    }
+
+    /// Map handle of the custom [`Asset`]
+    pub fn map_handle<A: Asset>(&self, handle: Handle<A>) -> Handle<A> {
+        Handle::from_index(self.map_index(A::TYPE, handle.unwrap()))
+    }
+
+    /// Map raw texture index, which is stored in materials
+    pub fn map_texture_index(&self, index: u32) -> u32 {
+        self.map(AssetHandle::<'T'>::from_index(index as usize)).unwrap() as u32
+    }
+
+    fn map_index(&self, asset_type: char, index: usize) -> usize {
+        if index == usize::MAX {
+            return index;
+        }
+
+        match asset_type {
+            'T' if index < self.builtin_textures => index,
+            'T' => index - self.builtin_textures + self.texture_offset,
+            'M' => index + self.material_offset,
+            'S' => index + self.sound_offset,
+            'L' => index + self.playlist_offset,
+            _ => self.custom_offsets.get(&asset_type).map_or(index, |offset| index + offset),
+        }
+    }
+}
+
+/// Trait for components, which store asset handles. They are remapped, when scene
+/// assets are merged into another [`AssetManager`](super::AssetManager)
+///
+/// # Usage example
+///
+/// ```rust
+/// #[derive(Clone, Serialize, Deserialize)]
+/// struct Portrait {
+///     image: Handle<Image>,
+///     voice: AssetHandle<'S'>,
+/// }
+///
+/// impl MapAssets for Portrait {
+///     fn map_assets(&mut self, mapping: &AssetMapping) {
+///         self.image.map_assets(mapping);
+///         self.voice.map_assets(mapping);
+///     }
+/// }
+///
+/// impl_ser_component!(map_assets: Portrait);
+/// ```
+pub trait MapAssets {
+    fn map_assets(&mut self, mapping: &AssetMapping);
+}
+
+impl<const TYPE: char> MapAssets for AssetHandle<TYPE> {
+    fn map_assets(&mut self, mapping: &AssetMapping) {
+        *self = mapping.map(*self);
+    }
+}
+
+impl<A: Asset> MapAssets for Handle<A> {
+    fn map_assets(&mut self, mapping: &AssetMapping) {
+        *self = mapping.map_handle(*self);
+    }
+}
+
+impl<T: MapAssets> MapAssets for Option<T> {
+    fn map_assets(&mut self, mapping: &AssetMapping) {
+        if let Some(value) = self {
+            value.map_assets(mapping);
+        }
+    }
+}
+
+impl<T: MapAssets> MapAssets for Vec<T> {
+    fn map_assets(&mut self, mapping: &AssetMapping) {
+        for value in self {
+            value.map_assets(mapping);
+        }
+    }
+}
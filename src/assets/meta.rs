@@ -0,0 +1,182 @@
+use std::path::{Path, PathBuf};
+
+use kira::sound::{Region, static_sound::StaticSoundSettings, streaming::StreamingSoundSettings};
+use serde::{Serialize, Deserialize, de::DeserializeOwned};
+use ron::ser::PrettyConfig;
+use walkdir::WalkDir;
+
+use crate::audio::Volume;
+use crate::error::FlatboxResult;
+#[cfg(feature = "render")]
+use crate::render::{Filter, TextureType, ColorSpace, CubemapLayout};
+
+use super::source::Vfs;
+
+/// Get path of the asset's sidecar file, e.g. `grass.png.meta` for `grass.png`
+pub fn meta_path<P: AsRef<Path>>(asset_path: P) -> PathBuf {
+    let mut path = asset_path.as_ref().as_os_str().to_owned();
+    path.push(".meta");
+    PathBuf::from(path)
+}
+
+/// Import settings of the asset, which are stored in the `.meta` RON sidecar file next to it
+pub trait AssetMeta: Serialize + DeserializeOwned + Default {
+    /// Read sidecar of the asset. Returns `None` if it's missing or invalid
+    fn load<P: AsRef<Path>>(asset_path: P) -> Option<Self> {
+        let path = meta_path(asset_path);
+        let data = Vfs::read_to_string(&path).ok()?;
+
+        match ron::from_str(&data) {
+            Ok(meta) => Some(meta),
+            Err(error) => {
+                log::error!("Invalid asset meta '{}': {error}", path.display());
+                None
+            },
+        }
+    }
+
+    /// Read sidecar of the asset. Returns the default settings if it's missing or invalid.
+    /// Sidecars are never written at runtime, since assets may be read from a read-only
+    /// [`AssetPack`](super::AssetPack) or an in-memory mount. Use [`generate_meta`] to create them
+    fn load_or_default<P: AsRef<Path>>(asset_path: P) -> Self {
+        Self::load(asset_path).unwrap_or_default()
+    }
+
+    /// Write sidecar of the asset to disk
+    fn save<P: AsRef<Path>>(&self, asset_path: P) -> FlatboxResult<()> {
+        let data = ron::ser::to_string_pretty(self, PrettyConfig::default())?;
+        std::fs::write(meta_path(asset_path), data)?;
+
+        Ok(())
+    }
+}
+
+/// Extensions of the textures, which [`generate_meta`] creates sidecars for
+pub const TEXTURE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "bmp", "tga", "hdr"];
+
+/// Extensions of the sounds, which [`generate_meta`] creates sidecars for
+pub const SOUND_EXTENSIONS: &[&str] = &["ogg", "wav", "mp3", "flac", "synth"];
+
+/// Create default sidecars for the textures and sounds of the `directory`, which don't
+/// have them yet. It's used by the asset tooling, e.g. `flatbox-pack meta`. Returns paths
+/// of the created sidecars
+pub fn generate_meta<P: AsRef<Path>>(directory: P) -> FlatboxResult<Vec<PathBuf>> {
+    let mut created = vec![];
+
+    for entry in WalkDir::new(directory).sort_by_file_name() {
+        let entry = entry.map_err(|e| crate::Result::yell(format!("Cannot read asset directory: {e}")))?;
+        let path = entry.path();
+        let extension = path.extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_lowercase())
+            .unwrap_or_default();
+
+        if !entry.file_type().is_file() || meta_path(path).exists() {
+            continue;
+        }
+
+        if TEXTURE_EXTENSIONS.contains(&extension.as_str()) {
+            #[cfg(feature = "render")]
+            TextureMeta::default().save(path)?;
+            #[cfg(not(feature = "render"))]
+            continue;
+        } else if SOUND_EXTENSIONS.contains(&extension.as_str()) {
+            SoundMeta::default().save(path)?;
+        } else {
+            continue;
+        }
+
+        created.push(meta_path(path));
+    }
+
+    Ok(created)
+}
+
+/// Import settings of the texture
+#[cfg(feature = "render")]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TextureMeta {
+    pub filter: Filter,
+    pub texture_type: TextureType,
+    /// Generate mipmap chain on uploading to GPU
+    pub generate_mipmaps: bool,
+    /// Use `Linear` for non-color data, e.g. normal or roughness maps
+    pub color_space: ColorSpace,
+    /// Arrangement of the cubemap faces in the image
+    pub cubemap_layout: CubemapLayout,
+}
+
+#[cfg(feature = "render")]
+impl Default for TextureMeta {
+    fn default() -> Self {
+        TextureMeta {
+            filter: Filter::Linear,
+            texture_type: TextureType::Plain,
+            generate_mipmaps: false,
+            color_space: ColorSpace::Srgb,
+            cubemap_layout: CubemapLayout::VerticalStrip,
+        }
+    }
+}
+
+#[cfg(feature = "render")]
+impl AssetMeta for TextureMeta {}
+
+/// Part of the sound in seconds. Missing `end` means the end of the sound
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SoundRegion {
+    pub start: f64,
+    pub end: Option<f64>,
+}
+
+impl From<SoundRegion> for Region {
+    fn from(region: SoundRegion) -> Self {
+        match region.end {
+            Some(end) => (region.start..end).into(),
+            None => (region.start..).into(),
+        }
+    }
+}
+
+/// Import settings of the sound
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SoundMeta {
+    /// Default volume of the sound
+    pub volume: Volume,
+    /// Playback speed factor
+    pub playback_rate: f64,
+    /// Looped part of the sound. The sound isn't looped, if it's `None`
+    pub loop_region: Option<SoundRegion>,
+}
+
+impl SoundMeta {
+    /// Convert to the playback settings
+    pub fn settings(&self) -> StaticSoundSettings {
+        StaticSoundSettings::new()
+            .volume(self.volume.amplitude())
+            .playback_rate(self.playback_rate)
+            .loop_region(self.loop_region.map(Region::from))
+    }
+
+    /// Convert to the playback settings of the streamed sound
+    pub fn streaming_settings(&self) -> StreamingSoundSettings {
+        StreamingSoundSettings::new()
+            .volume(self.volume.amplitude())
+            .playback_rate(self.playback_rate)
+            .loop_region(self.loop_region.map(Region::from))
+    }
+}
+
+impl Default for SoundMeta {
+    fn default() -> Self {
+        SoundMeta {
+            volume: Volume::default(),
+            playback_rate: 1.0,
+            loop_region: None,
+        }
+    }
+}
+
+impl AssetMeta for SoundMeta {}
@@ -2,11 +2,33 @@ pub mod asset_manager;
 pub mod scene;
 pub mod ser_component;
 pub mod save_load;
+pub mod save_slots;
+pub mod loading;
+pub mod storage;
+pub mod loader;
+pub mod hot_reload;
+pub mod source;
+pub mod pack;
+pub mod meta;
+pub mod mapping;
+pub mod format;
+pub mod prefab;
 
 pub use asset_manager::*;
 pub use scene::*;
 pub use ser_component::*;
 pub use save_load::*;
+pub use save_slots::*;
+pub use loading::*;
+pub use storage::*;
+pub use loader::*;
+pub use hot_reload::*;
+pub use source::*;
+pub use pack::*;
+pub use meta::*;
+pub use mapping::*;
+pub use format::*;
+pub use prefab::*;
 
 pub mod tar {
     pub use tar::*;
@@ -17,6 +39,7 @@ pub mod lz4 {
 }
 
 pub use typetag;
+pub use rmp_serde;
 
 use serde::{Serialize, Deserialize};
 
@@ -43,6 +66,11 @@ impl<const TYPE: char> AssetHandle<TYPE> {
     pub fn append(&mut self, count: usize) {
         self.0 += count;
     }
+
+    /// Get untyped [`AssetId`] of the handle
+    pub fn id(&self) -> AssetId {
+        AssetId::new(TYPE, self.0)
+    }
 }
 
 #[cfg(feature = "render")]
@@ -54,6 +82,12 @@ impl AssetHandle<'T'> {
     pub const BUILTIN_NORMAL: AssetHandle::<'T'> = AssetHandle(1);
 }
 
+impl<const TYPE: char> From<AssetHandle<TYPE>> for AssetId {
+    fn from(handle: AssetHandle<TYPE>) -> Self {
+        handle.id()
+    }
+}
+
 impl<const TYPE: char> From<AssetHandle<TYPE>> for u32 {
     fn from(value: AssetHandle<TYPE>) -> Self {
         value.unwrap() as u32
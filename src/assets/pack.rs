@@ -0,0 +1,258 @@
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+use serde::{Serialize, Deserialize};
+use thiserror::Error;
+use walkdir::WalkDir;
+
+use crate::error::FlatboxResult;
+
+/// Name of the manifest file inside the asset pack
+pub const PACK_MANIFEST: &str = "flatbox-pack.ron";
+
+/// File, stored in the asset pack
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PackEntry {
+    /// Path relative to the packed directory
+    pub path: PathBuf,
+    /// Uncompressed size in bytes
+    pub size: u64,
+    /// CRC-32 checksum of the file content
+    pub crc32: u32,
+}
+
+/// List of files, stored in the asset pack
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PackManifest {
+    pub files: Vec<PackEntry>,
+}
+
+impl PackManifest {
+    pub fn get<P: AsRef<Path>>(&self, path: P) -> Option<&PackEntry> {
+        self.files.iter().find(|e| e.path == path.as_ref())
+    }
+
+    /// Total uncompressed size of the packed files
+    pub fn total_size(&self) -> u64 {
+        self.files.iter().map(|e| e.size).sum()
+    }
+}
+
+/// Problem, found during asset pack verifying
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+pub enum PackIssue {
+    #[error("File '{0}' is listed in the manifest, but missing")]
+    Missing(PathBuf),
+    #[error("File '{0}' isn't listed in the manifest")]
+    Unlisted(PathBuf),
+    #[error("File '{path}' has size {actual}, but {expected} is expected")]
+    SizeMismatch { path: PathBuf, expected: u64, actual: u64 },
+    #[error("File '{path}' has checksum {actual:08x}, but {expected:08x} is expected")]
+    HashMismatch { path: PathBuf, expected: u32, actual: u32 },
+}
+
+/// Compressed `.tar.lz4` archive of the asset directory with the [`PackManifest`].
+/// Packs can be mounted with [`ArchiveSource`](super::ArchiveSource):
+///
+/// ```rust
+/// AssetPack::build("assets", "assets.tar.lz4")?;
+/// // ...
+/// Vfs::mount("assets", ArchiveSource::open("assets.tar.lz4")?, 0);
+/// ```
+pub struct AssetPack;
+
+impl AssetPack {
+    /// Pack all files of the `directory` to the `output` archive. The `output` itself
+    /// is skipped, if it's inside of the `directory`. The directory must not contain
+    /// the file named [`PACK_MANIFEST`] at its root
+    pub fn build<P: AsRef<Path>, Q: AsRef<Path>>(directory: P, output: Q) -> FlatboxResult<PackManifest> {
+        let directory = directory.as_ref();
+        let output = output.as_ref();
+        let mut manifest = PackManifest::default();
+
+        let entries = WalkDir::new(directory)
+            .sort_by_file_name()
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| crate::Result::yell(format!("Cannot read asset directory: {e}")))?;
+
+        let output_path = canonical_output(output)?;
+        let mut files = vec![];
+
+        for entry in entries {
+            if !entry.file_type().is_file() || entry.path().canonicalize()? == output_path {
+                continue;
+            }
+
+            let path = entry.path()
+                .strip_prefix(directory)
+                .map_err(|e| crate::Result::yell(e.to_string()))?
+                .to_owned();
+
+            if path == Path::new(PACK_MANIFEST) {
+                return Err(crate::Result::yell(format!(
+                    "File '{}' would shadow the pack manifest, rename it",
+                    entry.path().display(),
+                )));
+            }
+
+            files.push((entry.into_path(), path));
+        }
+
+        let encoder = lz4::EncoderBuilder::new()
+            .level(4)
+            .build(File::create(output)?)?;
+        let mut archive = tar::Builder::new(encoder);
+
+        for (source, path) in files {
+            let data = std::fs::read(source)?;
+            archive.append_data(&mut create_header(data.len()), &path, data.as_slice())?;
+
+            manifest.files.push(PackEntry {
+                path,
+                size: data.len() as u64,
+                crc32: crc32fast::hash(&data),
+            });
+        }
+
+        let manifest_data = ron::ser::to_string_pretty(&manifest, ron::ser::PrettyConfig::default())?;
+        archive.append_data(&mut create_header(manifest_data.len()), PACK_MANIFEST, manifest_data.as_bytes())?;
+
+        let (_, result) = archive.into_inner()?.finish();
+        result?;
+
+        Ok(manifest)
+    }
+
+    /// Read manifest of the pack
+    pub fn manifest<P: AsRef<Path>>(pack: P) -> FlatboxResult<PackManifest> {
+        let mut manifest = None;
+
+        AssetPack::for_each(pack, |path, data| {
+            if path == Path::new(PACK_MANIFEST) {
+                manifest = Some(ron::de::from_bytes(data)?);
+            }
+            Ok(())
+        })?;
+
+        manifest.ok_or(crate::Result::yell("Asset pack has no manifest"))
+    }
+
+    /// Extract all packed files to the `directory`
+    pub fn extract<P: AsRef<Path>, Q: AsRef<Path>>(pack: P, directory: Q) -> FlatboxResult<PackManifest> {
+        let directory = directory.as_ref();
+        let mut manifest = None;
+
+        AssetPack::for_each(pack, |path, data| {
+            if path == Path::new(PACK_MANIFEST) {
+                manifest = Some(ron::de::from_bytes(data)?);
+                return Ok(());
+            }
+
+            let target = directory.join(path);
+            if let Some(parent) = target.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            File::create(target)?.write_all(data)?;
+
+            Ok(())
+        })?;
+
+        manifest.ok_or(crate::Result::yell("Asset pack has no manifest"))
+    }
+
+    /// Check packed files against the manifest. Returns found problems
+    pub fn verify<P: AsRef<Path>>(pack: P) -> FlatboxResult<Vec<PackIssue>> {
+        let mut manifest: Option<PackManifest> = None;
+        let mut files = vec![];
+
+        AssetPack::for_each(pack, |path, data| {
+            if path == Path::new(PACK_MANIFEST) {
+                manifest = Some(ron::de::from_bytes(data)?);
+            } else {
+                files.push((path.to_owned(), data.len() as u64, crc32fast::hash(data)));
+            }
+            Ok(())
+        })?;
+
+        let manifest = manifest.ok_or(crate::Result::yell("Asset pack has no manifest"))?;
+        let mut issues = vec![];
+
+        for (path, size, crc32) in &files {
+            match manifest.get(path) {
+                None => issues.push(PackIssue::Unlisted(path.clone())),
+                Some(entry) if entry.size != *size => issues.push(PackIssue::SizeMismatch {
+                    path: path.clone(),
+                    expected: entry.size,
+                    actual: *size,
+                }),
+                Some(entry) if entry.crc32 != *crc32 => issues.push(PackIssue::HashMismatch {
+                    path: path.clone(),
+                    expected: entry.crc32,
+                    actual: *crc32,
+                }),
+                _ => {},
+            }
+        }
+
+        for entry in &manifest.files {
+            if !files.iter().any(|(path, _, _)| *path == entry.path) {
+                issues.push(PackIssue::Missing(entry.path.clone()));
+            }
+        }
+
+        Ok(issues)
+    }
+
+    /// Decompress the pack and call `f` for every regular file
+    fn for_each<P, F>(pack: P, mut f: F) -> FlatboxResult<()>
+    where
+        P: AsRef<Path>,
+        F: FnMut(&Path, &[u8]) -> FlatboxResult<()>,
+    {
+        let decoded = lz4::Decoder::new(File::open(pack)?)?;
+        let mut archive = tar::Archive::new(decoded);
+
+        for entry in archive.entries()? {
+            let mut entry = entry?;
+            if entry.header().entry_type() != tar::EntryType::Regular {
+                continue;
+            }
+
+            let path = entry.path()?.into_owned();
+            if path.is_absolute() || path.components().any(|c| c == std::path::Component::ParentDir) {
+                return Err(crate::Result::yell(format!("Asset pack contains invalid path '{}'", path.display())));
+            }
+
+            let mut data = vec![];
+            entry.read_to_end(&mut data)?;
+
+            f(&path, &data)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Absolute path of the `output` file, which may not exist yet
+fn canonical_output(output: &Path) -> FlatboxResult<PathBuf> {
+    let parent = match output.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+
+    let name = output.file_name()
+        .ok_or_else(|| crate::Result::yell(format!("Invalid pack path '{}'", output.display())))?;
+
+    Ok(parent.canonicalize()?.join(name))
+}
+
+pub(crate) fn create_header(size: usize) -> tar::Header {
+    let mut header = tar::Header::new_gnu();
+    header.set_entry_type(tar::EntryType::Regular);
+    header.set_size(size as u64);
+    header.set_mode(0o644);
+
+    header
+}
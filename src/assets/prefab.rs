@@ -0,0 +1,300 @@
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use serde::{Serialize, Deserialize};
+
+use crate::ecs::*;
+use crate::error::FlatboxResult;
+
+use super::{
+    asset_manager::AssetManager,
+    format::SerialFormat,
+    loader::AssetLoader,
+    scene::*,
+    ser_component::SerializableComponent,
+    storage::{Asset, Handle},
+};
+
+/// Maximal depth of the prefab inheritance chain
+const MAX_PREFAB_DEPTH: usize = 16;
+
+/// Reusable entity group, based on the [`Scene`] or another prefab, with per-instance patches.
+/// The base is resolved on the first [`spawn`](#method.spawn) and cached. Spawned instances
+/// are tracked with [`PrefabInstance`], so changes of the base are re-applied to them by the
+/// [`HotReload`](super::HotReload) extension, unless they override the same field
+///
+/// # Usage example
+///
+/// `door.prefab`:
+///
+/// ```ron
+/// (
+///     base: "assets/prefabs/door.ron",
+///     overrides: [
+///         (
+///             entity: 0,
+///             fields: [
+///                 (component: "Transform", path: "translation.1", value: 2.5),
+///             ],
+///         ),
+///     ],
+/// )
+/// ```
+///
+/// ```rust
+/// asset_manager.add_loader(PrefabLoader);
+/// let door: Handle<Prefab> = asset_manager.load("assets/door.prefab")?;
+/// // ...
+/// Prefab::spawn(door, &mut world, &mut asset_manager)?;
+/// ```
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Prefab {
+    /// Path of the base scene or prefab (with `.prefab` extension)
+    pub base: PathBuf,
+    /// Patches of the base entities
+    pub overrides: Vec<EntityOverride>,
+    /// Entities, which are spawned along with the base ones
+    pub additions: Vec<SerializableEntity>,
+    /// Resolved prefab, which assets have been merged on the first spawn
+    #[serde(skip)]
+    prepared: Option<Arc<PreparedScene>>,
+    /// Files of the inheritance chain
+    #[serde(skip)]
+    bases: Vec<PathBuf>,
+}
+
+/// Component of the first entity of the spawned [`Prefab`] instance, which tracks all its entities
+#[derive(Clone, Debug)]
+pub struct PrefabInstance {
+    pub prefab: Handle<Prefab>,
+    pub entities: Vec<Entity>,
+}
+
+/// Patch of the base entity
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EntityOverride {
+    /// Index of the entity in the base
+    pub entity: usize,
+    /// Components, which replace the base ones of the same type or are added to the entity
+    pub components: Vec<Arc<dyn SerializableComponent + 'static>>,
+    /// Fields of the base components
+    pub fields: Vec<FieldOverride>,
+    /// Names of the removed components
+    pub remove: Vec<String>,
+}
+
+/// Value of the component field
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct FieldOverride {
+    /// Serialized name of the component, e.g. `Transform`
+    pub component: String,
+    /// Dot-separated path of the field. Sequence elements are accessed by index, e.g. `translation.1`
+    pub path: String,
+    pub value: ron::Value,
+}
+
+impl Asset for Prefab {
+    const TYPE: char = 'P';
+}
+
+impl Prefab {
+    /// Load prefab of any [`SerialFormat`]
+    pub fn load<P: AsRef<Path>>(path: P) -> FlatboxResult<Self> {
+        SerialFormat::load(path)
+    }
+
+    /// Load the base and apply patches to it
+    pub fn resolve(&self) -> FlatboxResult<Scene> {
+        self.resolve_depth(0, &mut vec![])
+    }
+
+    /// Spawn instance of the prefab along with existing entities. The resolved base is cached,
+    /// so its assets are merged into the `asset_manager` only once. Returns spawned entities
+    pub fn spawn(
+        handle: Handle<Prefab>,
+        world: &mut World,
+        asset_manager: &mut AssetManager,
+    ) -> FlatboxResult<Vec<Entity>> {
+        let prefab = asset_manager.get(handle)
+            .ok_or(crate::Result::yell(format!("Prefab {handle:?} isn't loaded")))?;
+
+        let prepared = match &prefab.prepared {
+            Some(prepared) => prepared.clone(),
+            None => Prefab::prepare(handle, asset_manager)?,
+        };
+
+        let entities = world.spawn_instance(&prepared, None);
+
+        if let Some(&first) = entities.first() {
+            world.insert_one(first, PrefabInstance { prefab: handle, entities: entities.clone() })
+                .expect("Spawned entity is missing");
+        }
+
+        Ok(entities)
+    }
+
+    /// Resolve the base again and insert its components into the spawned instances.
+    /// Overridden fields keep the values of the overrides
+    pub fn reapply(
+        handle: Handle<Prefab>,
+        world: &mut World,
+        asset_manager: &mut AssetManager,
+    ) -> FlatboxResult<()> {
+        let prepared = Prefab::prepare(handle, asset_manager)?;
+
+        let instances = world.query_mut::<&PrefabInstance>()
+            .into_iter()
+            .filter(|(_, instance)| instance.prefab == handle)
+            .map(|(_, instance)| instance.entities.clone())
+            .collect::<Vec<_>>();
+
+        for entities in instances {
+            world.update_instance(&prepared, &entities);
+        }
+
+        Ok(())
+    }
+
+    /// Check whether the file is the base of the prefab or of any of its bases
+    pub fn depends_on<P: AsRef<Path>>(&self, path: P) -> bool {
+        self.bases.iter().any(|base| base == path.as_ref())
+    }
+
+    /// Resolve the prefab, merge its assets and cache it. Bases are watched by the [`AssetWatcher`](super::AssetWatcher)
+    fn prepare(handle: Handle<Prefab>, asset_manager: &mut AssetManager) -> FlatboxResult<Arc<PreparedScene>> {
+        let mut bases = vec![];
+        let scene = asset_manager.get(handle)
+            .ok_or(crate::Result::yell(format!("Prefab {handle:?} isn't loaded")))?
+            .resolve_depth(0, &mut bases)?;
+
+        let prepared = Arc::new(scene.prepare(asset_manager));
+
+        if let Some(watcher) = &mut asset_manager.watcher {
+            for base in &bases {
+                watcher.watch(base.clone());
+            }
+        }
+
+        if let Some(prefab) = asset_manager.get_mut(handle) {
+            prefab.prepared = Some(prepared.clone());
+            prefab.bases = bases;
+        }
+
+        Ok(prepared)
+    }
+
+    fn resolve_depth(&self, depth: usize, bases: &mut Vec<PathBuf>) -> FlatboxResult<Scene> {
+        if depth >= MAX_PREFAB_DEPTH {
+            return Err(crate::Result::yell(format!("Prefab inheritance of '{}' is too deep", self.base.display())));
+        }
+
+        bases.push(self.base.clone());
+
+        let mut scene = match self.base.extension().and_then(|e| e.to_str()) {
+            Some("prefab") => Prefab::load(&self.base)?.resolve_depth(depth + 1, bases)?,
+            _ => Scene::load(&self.base)?,
+        };
+
+        for patch in &self.overrides {
+            let entity = scene.entities.get_mut(patch.entity)
+                .ok_or(crate::Result::yell(format!("Prefab base '{}' has no entity {}", self.base.display(), patch.entity)))?;
+
+            patch.apply(entity)?;
+        }
+
+        for entity in &self.additions {
+            scene.entities.push(SerializableEntity {
+                components: entity.components.clone(),
+            });
+        }
+
+        Ok(scene)
+    }
+}
+
+impl EntityOverride {
+    fn apply(&self, entity: &mut SerializableEntity) -> FlatboxResult<()> {
+        let mut components = entity.components.iter()
+            .map(|c| to_tree(&**c))
+            .collect::<FlatboxResult<Vec<_>>>()?;
+
+        components.retain(|c| !self.remove.iter().any(|name| Some(name.as_str()) == component_name(c)));
+
+        for component in &self.components {
+            let tree = to_tree(&**component)?;
+            let name = component_name(&tree).map(String::from);
+
+            match components.iter_mut().find(|c| component_name(c).map(String::from) == name) {
+                Some(old) => *old = tree,
+                None => components.push(tree),
+            }
+        }
+
+        for field in &self.fields {
+            let component = components.iter_mut()
+                .find(|c| component_name(c) == Some(field.component.as_str()))
+                .ok_or(crate::Result::yell(format!("Overridden component '{}' is missing", field.component)))?;
+
+            let target = field_mut(component, &field.path)
+                .ok_or(crate::Result::yell(format!("Component '{}' has no field '{}'", field.component, field.path)))?;
+
+            *target = to_tree(&field.value)?;
+        }
+
+        entity.components = components.iter()
+            .map(|tree| {
+                let mut data = vec![];
+                rmpv::encode::write_value(&mut data, tree)
+                    .map_err(|e| crate::Result::yell(e.to_string()))?;
+
+                let component: Box<dyn SerializableComponent> = rmp_serde::from_slice(&data)?;
+                Ok(Arc::from(component))
+            })
+            .collect::<FlatboxResult<_>>()?;
+
+        Ok(())
+    }
+}
+
+/// Loader of the [`Prefab`]s from `.prefab` files
+pub struct PrefabLoader;
+
+impl AssetLoader for PrefabLoader {
+    type Asset = Prefab;
+
+    fn extensions(&self) -> &[&str] {
+        &["prefab"]
+    }
+
+    fn load(&self, bytes: &[u8], _path: &Path) -> FlatboxResult<Prefab> {
+        SerialFormat::deserialize(bytes)
+    }
+}
+
+/// Convert value to the format-independent tree
+fn to_tree<T: Serialize + ?Sized>(value: &T) -> FlatboxResult<rmpv::Value> {
+    let data = rmp_serde::to_vec_named(value)?;
+    rmpv::decode::read_value(&mut data.as_slice())
+        .map_err(|e| crate::Result::yell(e.to_string()))
+}
+
+/// Serialized name of the component, stored in its `component` tag
+fn component_name(tree: &rmpv::Value) -> Option<&str> {
+    tree.as_map()?
+        .iter()
+        .find(|(key, _)| key.as_str() == Some("component"))
+        .and_then(|(_, name)| name.as_str())
+}
+
+fn field_mut<'a>(tree: &'a mut rmpv::Value, path: &str) -> Option<&'a mut rmpv::Value> {
+    path.split('.').try_fold(tree, |node, segment| match node {
+        rmpv::Value::Map(entries) => entries.iter_mut()
+            .find(|(key, _)| key.as_str() == Some(segment))
+            .map(|(_, value)| value),
+        rmpv::Value::Array(items) => segment.parse::<usize>().ok()
+            .and_then(move |i| items.get_mut(i)),
+        _ => None,
+    })
+}
@@ -1,67 +1,446 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::fs::File;
+use std::io::Read;
+use std::marker::PhantomData;
+use std::path::Path;
+
+use serde::{Serialize, Deserialize, Deserializer};
+use serde::de::{self, DeserializeSeed, SeqAccess, Visitor};
+use ron::ser::PrettyConfig;
+use thiserror::Error;
+
 use crate::ecs::*;
 use crate::error::FlatboxResult;
 use crate::assets::asset_manager::AssetManager;
 use crate::physics::physics_handler::PhysicsHandler;
 
+use super::format::SerialFormat;
+use super::pack::create_header;
+
+/// Version of the save archive layout. Saves without manifest have version `0`
+pub const SAVE_FORMAT_VERSION: u32 = 1;
+
+/// Name of the manifest entry inside the save archive
+pub const SAVE_MANIFEST: &str = "manifest.ron";
+
 pub trait SaveLoad {
+    /// Save game in the [`SerialFormat::Ron`] format
     fn save<P: AsRef<std::path::Path>>(
         &mut self,
         world: &World,
         asset_manager: &AssetManager,
         physics_handler: &PhysicsHandler,
         path: P,
+    ) -> FlatboxResult<()> {
+        self.save_as(world, asset_manager, physics_handler, path, SerialFormat::Ron)
+    }
+
+    fn save_as<P: AsRef<std::path::Path>>(
+        &mut self,
+        world: &World,
+        asset_manager: &AssetManager,
+        physics_handler: &PhysicsHandler,
+        path: P,
+        format: SerialFormat,
     ) -> FlatboxResult<()>;
-    
+
+    /// Load save of any [`SerialFormat`]
     fn load<P: AsRef<std::path::Path>>(
         &mut self,
         path: P,
     ) -> FlatboxResult<(World, AssetManager, PhysicsHandler)>;
+
+    /// Convert save to the `format`
+    fn convert<P: AsRef<std::path::Path>, Q: AsRef<std::path::Path>>(
+        &mut self,
+        input: P,
+        output: Q,
+        format: SerialFormat,
+    ) -> FlatboxResult<()> {
+        let (world, asset_manager, physics_handler) = self.load(input)?;
+        self.save_as(&world, &asset_manager, &physics_handler, output, format)
+    }
+}
+
+/// Error during reading the save archive
+#[non_exhaustive]
+#[derive(Debug, Error)]
+pub enum SaveError {
+    #[error("Save archive is invalid: {0}")]
+    InvalidArchive(String),
+    #[error("Save entry '{0}' is missing")]
+    MissingEntry(String),
+    #[error("Save entry '{entry}' is corrupted: checksum is {actual:08x}, but {expected:08x} is expected")]
+    ChecksumMismatch { entry: String, expected: u32, actual: u32 },
+    #[error("Save entry '{entry}' is truncated or padded: its size is {actual} bytes, but {expected} are expected")]
+    SizeMismatch { entry: String, expected: u64, actual: u64 },
+    #[error("Save entry '{entry}' is invalid: {message}")]
+    InvalidEntry { entry: String, message: String },
+    #[error("Save format version {found} is newer than supported {supported}")]
+    UnsupportedVersion { found: u32, supported: u32 },
+}
+
+impl SaveError {
+    pub fn invalid<E: Display>(entry: &str, error: E) -> Self {
+        SaveError::InvalidEntry {
+            entry: entry.to_owned(),
+            message: error.to_string(),
+        }
+    }
+}
+
+/// Entry, stored in the save archive
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SaveEntry {
+    pub name: String,
+    /// Size in bytes
+    pub size: u64,
+    /// CRC-32 checksum of the entry content
+    pub crc32: u32,
+}
+
+/// Header of the save archive
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SaveManifest {
+    /// Version of the archive layout, see [`SAVE_FORMAT_VERSION`]
+    pub format_version: u32,
+    /// Version of the game, which has written the save
+    pub game_version: String,
+    pub entries: Vec<SaveEntry>,
+}
+
+impl SaveManifest {
+    pub fn get(&self, name: &str) -> Option<&SaveEntry> {
+        self.entries.iter().find(|e| e.name == name)
+    }
+}
+
+/// Compressed `.tar.lz4` save archive with the [`SaveManifest`]. It's used
+/// by the loaders, created with [`impl_save_load!`](crate::impl_save_load)
+pub struct SaveArchive {
+    pub manifest: SaveManifest,
+    entries: HashMap<String, Vec<u8>>,
+}
+
+impl SaveArchive {
+    pub fn new<S: Into<String>>(game_version: S) -> Self {
+        SaveArchive {
+            manifest: SaveManifest {
+                format_version: SAVE_FORMAT_VERSION,
+                game_version: game_version.into(),
+                entries: vec![],
+            },
+            entries: HashMap::new(),
+        }
+    }
+
+    /// Add or replace entry of the archive
+    pub fn insert<S: Into<String>>(&mut self, name: S, data: Vec<u8>) {
+        let name = name.into();
+        let entry = SaveEntry {
+            name: name.clone(),
+            size: data.len() as u64,
+            crc32: crc32fast::hash(&data),
+        };
+
+        match self.manifest.entries.iter_mut().find(|e| e.name == name) {
+            Some(old) => *old = entry,
+            None => self.manifest.entries.push(entry),
+        }
+
+        self.entries.insert(name, data);
+    }
+
+    pub fn get(&self, name: &str) -> Result<&[u8], SaveError> {
+        self.entries.get(name)
+            .map(|data| data.as_slice())
+            .ok_or(SaveError::MissingEntry(name.to_owned()))
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.entries.contains_key(name)
+    }
+
+    /// Deserialize entry of the archive. Its [`SerialFormat`] is detected automatically
+    pub fn deserialize<T: for<'de> Deserialize<'de>>(&self, name: &str) -> Result<T, SaveError> {
+        SerialFormat::deserialize(self.get(name)?).map_err(|e| SaveError::invalid(name, e))
+    }
+
+    /// Write the archive to disk
+    pub fn write<P: AsRef<Path>>(&self, path: P) -> FlatboxResult<()> {
+        let encoder = lz4::EncoderBuilder::new()
+            .level(4)
+            .build(File::create(path)?)?;
+        let mut archive = tar::Builder::new(encoder);
+
+        let manifest = ron::ser::to_string_pretty(&self.manifest, PrettyConfig::default())?;
+        archive.append_data(&mut create_header(manifest.len()), SAVE_MANIFEST, manifest.as_bytes())?;
+
+        for entry in &self.manifest.entries {
+            let data = &self.entries[&entry.name];
+            archive.append_data(&mut create_header(data.len()), &entry.name, data.as_slice())?;
+        }
+
+        let (_, result) = archive.into_inner()?.finish();
+        result?;
+
+        Ok(())
+    }
+
+    /// Read the archive from disk and check entries against the manifest
+    pub fn read<P: AsRef<Path>>(path: P) -> FlatboxResult<Self> {
+        let decoded = lz4::Decoder::new(File::open(path)?)?;
+        let mut archive = tar::Archive::new(decoded);
+        let mut entries = HashMap::new();
+
+        let files = archive.entries()
+            .map_err(|e| SaveError::InvalidArchive(e.to_string()))?;
+
+        for file in files {
+            let mut file = file.map_err(|e| SaveError::InvalidArchive(e.to_string()))?;
+            if file.header().entry_type() != tar::EntryType::Regular {
+                continue;
+            }
+
+            let name = file.path()
+                .map_err(|e| SaveError::InvalidArchive(e.to_string()))?
+                .to_str()
+                .ok_or(SaveError::InvalidArchive(String::from("entry name isn't valid UTF-8")))?
+                .to_owned();
+
+            let mut data = vec![];
+            file.read_to_end(&mut data)
+                .map_err(|e| SaveError::InvalidArchive(e.to_string()))?;
+
+            entries.insert(name, data);
+        }
+
+        let manifest = match entries.remove(SAVE_MANIFEST) {
+            Some(data) => {
+                let manifest: SaveManifest = ron::de::from_bytes(&data)
+                    .map_err(|e| SaveError::invalid(SAVE_MANIFEST, e))?;
+
+                if manifest.format_version > SAVE_FORMAT_VERSION {
+                    return Err(SaveError::UnsupportedVersion {
+                        found: manifest.format_version,
+                        supported: SAVE_FORMAT_VERSION,
+                    }.into());
+                }
+
+                for entry in &manifest.entries {
+                    let data = entries.get(&entry.name)
+                        .ok_or(SaveError::MissingEntry(entry.name.clone()))?;
+
+                    if data.len() as u64 != entry.size {
+                        return Err(SaveError::SizeMismatch {
+                            entry: entry.name.clone(),
+                            expected: entry.size,
+                            actual: data.len() as u64,
+                        }.into());
+                    }
+
+                    let actual = crc32fast::hash(data);
+                    if actual != entry.crc32 {
+                        return Err(SaveError::ChecksumMismatch {
+                            entry: entry.name.clone(),
+                            expected: entry.crc32,
+                            actual,
+                        }.into());
+                    }
+                }
+
+                manifest
+            },
+            None => {
+                log::warn!("Save archive has no manifest, so it's loaded without verifying");
+
+                SaveManifest {
+                    format_version: 0,
+                    game_version: String::new(),
+                    entries: entries.iter().map(|(name, data)| SaveEntry {
+                        name: name.clone(),
+                        size: data.len() as u64,
+                        crc32: crc32fast::hash(data),
+                    }).collect(),
+                }
+            },
+        };
+
+        Ok(SaveArchive { manifest, entries })
+    }
+}
+
+thread_local! {
+    static LOADED_GAME_VERSION: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+/// Scope, during which migrations of [`impl_save_load!`](crate::impl_save_load) receive
+/// the game version of the loaded save
+pub struct GameVersionScope(());
+
+impl GameVersionScope {
+    pub fn new<S: Into<String>>(game_version: S) -> Self {
+        LOADED_GAME_VERSION.with(|version| *version.borrow_mut() = Some(game_version.into()));
+        GameVersionScope(())
+    }
+}
+
+impl Drop for GameVersionScope {
+    fn drop(&mut self) {
+        LOADED_GAME_VERSION.with(|version| *version.borrow_mut() = None);
+    }
+}
+
+/// Deserialize a column of `entity_count` `Old` components from `seq` and write them into
+/// `out` as `New` ones, converted with `migrate`. It receives the game version of the save,
+/// which is set by [`GameVersionScope`]. Used for migrations in [`impl_save_load!`](crate::impl_save_load)
+pub fn deserialize_migrated_column<'de, Old, New, A, F>(
+    entity_count: u32,
+    seq: &mut A,
+    out: &mut ColumnBatchBuilder,
+    migrate: F,
+) -> Result<(), A::Error>
+where
+    Old: Deserialize<'de>,
+    New: Component,
+    A: SeqAccess<'de>,
+    F: Fn(Old, &str) -> New,
+{
+    struct MigratedColumn<'a, Old, New, F> {
+        entity_count: u32,
+        out: &'a mut ColumnBatchBuilder,
+        migrate: F,
+        marker: PhantomData<fn() -> (Old, New)>,
+    }
+
+    impl<'de, Old, New, F> DeserializeSeed<'de> for MigratedColumn<'_, Old, New, F>
+    where
+        Old: Deserialize<'de>,
+        New: Component,
+        F: Fn(Old, &str) -> New,
+    {
+        type Value = ();
+
+        fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+            deserializer.deserialize_tuple(self.entity_count as usize, self)
+        }
+    }
+
+    impl<'de, Old, New, F> Visitor<'de> for MigratedColumn<'_, Old, New, F>
+    where
+        Old: Deserialize<'de>,
+        New: Component,
+        F: Fn(Old, &str) -> New,
+    {
+        type Value = ();
+
+        fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+            write!(
+                formatter,
+                "a set of {} {} values",
+                self.entity_count,
+                std::any::type_name::<Old>(),
+            )
+        }
+
+        fn visit_seq<S: SeqAccess<'de>>(self, mut seq: S) -> Result<(), S::Error> {
+            let entity_count = self.entity_count;
+            let game_version = LOADED_GAME_VERSION.with(|version| version.borrow().clone().unwrap_or_default());
+            let mut out = self.out.writer::<New>()
+                .ok_or_else(|| de::Error::custom("unexpected component type"))?;
+
+            while let Some(component) = seq.next_element::<Old>()? {
+                if out.push((self.migrate)(component, &game_version)).is_err() {
+                    return Err(de::Error::custom(format!("more than {entity_count} components in the column")));
+                }
+            }
+
+            if out.fill() < entity_count {
+                return Err(de::Error::custom(format!("{} components in the column, but {entity_count} are expected", out.fill())));
+            }
+
+            Ok(())
+        }
+    }
+
+    seq.next_element_seed(MigratedColumn::<Old, New, F> {
+        entity_count,
+        out,
+        migrate,
+        marker: PhantomData,
+    })?
+    .ok_or_else(|| de::Error::custom("missing column of components"))
 }
 
-/// Macro that is used to create custom [`SaveLoad`]ers, 
+/// Macro that is used to create custom [`SaveLoad`]ers,
 /// that are capable of saving and loading individual serializable
-/// components from the [`World`], scene's [`PhysicsHandler`] and 
+/// components from the [`World`], scene's [`PhysicsHandler`] and
 /// [`AssetManager`]
-/// 
+///
+/// Saves are written as [`SaveArchive`]s, which entries are verified on loading.
+/// Optional `migrations` upgrade components of the older saves: `Old => New` reads
+/// columns, saved with `Old` id, and converts them with `New: From<Old>`. Use
+/// `Old => New: migrate` to convert them with `migrate: Fn(Old, &str) -> New`,
+/// which also receives the game version of the save
+///
 /// # Usage example
-/// 
-/// ```rust 
+///
+/// ```rust
 /// #[derive(Serialize, Deserialize)]
 /// struct MyComponent(u32);
-/// 
+///
 /// #[derive(Default)]
 /// struct MySaveLoader {
 ///     components: Vec<String>, // required field
 /// }
-/// 
+///
 /// impl_save_load! {
-///     loader: MySaveLoader, 
+///     loader: MySaveLoader,
 ///     components: [
-///         Camera, 
-///         Timer, 
+///         Camera,
+///         Timer,
 ///         Transform,
 ///         AssetHandle<'M'>,
 ///         MyComponent
 ///     ]
 /// }
-/// 
+///
+/// // Components, which store entity references, must also implement
+/// // `MapEntities` and be listed in `map_entities`
+/// impl_save_load! {
+///     loader: MyOtherSaveLoader,
+///     components: [Transform, Target],
+///     map_entities: [Target],
+///     migrations: [
+///         OldTarget => Target,
+///         OldHealth => Health: |old: OldHealth, version: &str| Health::new(old.0, version < "0.3"),
+///     ],
+///     game_version: env!("CARGO_PKG_VERSION")
+/// }
+///
 /// fn save_world(
 ///     world: Read<World>,
 /// ) -> FlatboxResult<()> {
 ///     let ws = MyWorldSaver::default();
-/// 
+///
 ///     ws.save("/path/to/save", &world)?;
 /// }
-/// 
+///
 /// ```
 #[macro_export]
 macro_rules! impl_save_load {
     {
-        loader: $ctx:ident, 
+        loader: $ctx:ident,
         components: [ $( $comp:ty ),+ ]
+        $(, map_entities: [ $( $mapped:ty ),+ ] )?
+        $(, migrations: [ $( $old:ty => $new:ty $( : $migrate:expr )? ),+ $(,)? ] )?
+        $(, game_version: $version:expr )?
+        $(,)?
     } => {
         impl SerializeContext for $ctx {
-            fn component_count(&self, archetype: &Archetype) -> usize {                
+            fn component_count(&self, archetype: &Archetype) -> usize {
                 archetype.component_types()
                     .filter(|&t|
                         $(
@@ -71,7 +450,7 @@ macro_rules! impl_save_load {
                     )
                     .count()
             }
-            
+
             fn serialize_component_ids<S: serde::ser::SerializeTuple>(
                 &mut self,
                 archetype: &Archetype,
@@ -80,10 +459,10 @@ macro_rules! impl_save_load {
                 $(
                     try_serialize_id::<$comp, _, _>(archetype, stringify!($comp), &mut out)?;
                 )*
-                
+
                 out.end()
             }
-            
+
             fn serialize_components<S: serde::ser::SerializeTuple>(
                 &mut self,
                 archetype: &Archetype,
@@ -92,11 +471,11 @@ macro_rules! impl_save_load {
                 $(
                     try_serialize::<$comp, _>(archetype, &mut out)?;
                 )*
-                
+
                 out.end()
             }
         }
-        
+
         impl DeserializeContext for $ctx {
             fn deserialize_component_ids<'de, A: serde::de::SeqAccess<'de>>(
                 &mut self,
@@ -105,21 +484,29 @@ macro_rules! impl_save_load {
                 self.components.clear();
                 let mut batch = ColumnBatchType::new();
                 while let Some(id) = seq.next_element::<String>()? {
-                    match id.as_str() {                        
-                        $(                            
+                    match id.as_str() {
+                        $(
                             stringify!($comp) => {
                                 batch.add::<$comp>();
                             }
                         )*
-                        
-                        _ => {},
+
+                        $($(
+                            stringify!($old) => {
+                                batch.add::<$new>();
+                            }
+                        )+)?
+
+                        _ => {
+                            $crate::warn!("Unknown component '{id}' in the save is skipped");
+                        },
                     }
                     self.components.push(id);
                 }
-                
+
                 Ok(batch)
             }
-            
+
             fn deserialize_components<'de, A: serde::de::SeqAccess<'de>>(
                 &mut self,
                 entity_count: u32,
@@ -128,129 +515,256 @@ macro_rules! impl_save_load {
             ) -> Result<(), A::Error> {
                 for component in &self.components {
                     match component.as_str() {
-                        $(                            
+                        $(
                             stringify!($comp) => {
                                 deserialize_column::<$comp, _>(entity_count, &mut seq, batch)?;
                             }
                         )*
-                        
-                        _ => {},
+
+                        $($(
+                            stringify!($old) => {
+                                deserialize_migrated_column::<$old, $new, _, _>(
+                                    entity_count,
+                                    &mut seq,
+                                    batch,
+                                    $crate::save_migration!($new $(, $migrate)?),
+                                )?;
+                            }
+                        )+)?
+
+                        _ => {
+                            seq.next_element::<serde::de::IgnoredAny>()?;
+                        },
                     }
                 }
-                
+
                 Ok(())
             }
 
         }
-        
+
         impl SaveLoad for $ctx {
-            fn save<P: AsRef<std::path::Path>>(
+            fn save_as<P: AsRef<std::path::Path>>(
                 &mut self,
                 world: &World,
                 asset_manager: &AssetManager,
                 physics_handler: &PhysicsHandler,
                 path: P,
+                format: SerialFormat,
             ) -> FlatboxResult<()> {
-                use std::mem::size_of_val;
-                use std::fs::File;
-                use std::io::Cursor;
-                use ron::ser::PrettyConfig;
-
-                let mut buf = vec![];                    
-                let mut ser = ron::Serializer::new(&mut buf, Some(PrettyConfig::new()))?;          
-                serialize_world(&world, self, &mut ser)?;
+                let game_version: &str = $crate::save_game_version!($($version)?);
 
-                let mut a = vec![];
-                let mut archive = tar::Builder::new(&mut a);
+                // Entry names are kept for compatibility; their format is detected by header
+                let mut archive = SaveArchive::new(game_version);
 
-                let world = &*buf;
-                let world_header = create_header("world.ron", world.len());
-                archive.append(&world_header, world)?;
+                let mut buf = vec![];
+                {
+                    let _scope = LocalEntityScope::new(world.iter().map(|e| e.entity()));
 
-                let assets = ron::ser::to_string_pretty(&asset_manager, PrettyConfig::default())?;
-                let assets_bytes = assets.as_bytes();
-                let assets_header = create_header("assets.ron", assets_bytes.len());
-                archive.append(&assets_header, assets_bytes)?;
-
-                let physics = ron::ser::to_string_pretty(&physics_handler, PrettyConfig::default())?;
-                let physics_bytes = physics.as_bytes();
-                let physics_header = create_header("physics.ron", physics_bytes.len());
-                archive.append(&physics_header, physics_bytes)?;
-
-                let mut inner = archive.into_inner()?;
-                let mut cursor = Cursor::new(inner);
-
-                let file = File::create(path)?;
-                let mut encoder = lz4::EncoderBuilder::new()
-                    .level(4)
-                    .build(file)?;  
-
-                std::io::copy(&mut cursor, &mut encoder)?; 
+                    match format {
+                        SerialFormat::Ron => {
+                            let mut ser = ron::Serializer::new(&mut buf, Some(ron::ser::PrettyConfig::new()))?;
+                            serialize_world(&world, self, &mut ser)?;
+                        },
+                        SerialFormat::Binary => {
+                            buf.extend_from_slice(BINARY_MAGIC);
+                            serialize_world(&world, self, &mut binary_serializer(&mut buf))?;
+                        },
+                    }
+                }
+                archive.insert("world.ron", buf);
 
-                let (_, result) = encoder.finish();
-                
-                result?;
+                let entities = world.iter()
+                    .map(|e| e.entity().to_bits().get())
+                    .collect::<Vec<_>>();
+                archive.insert("entities.ron", format.serialize(&entities)?);
+                archive.insert("assets.ron", format.serialize(asset_manager)?);
+                archive.insert("physics.ron", format.serialize(physics_handler)?);
 
-                Ok(())
+                archive.write(path)
             }
-            
+
             fn load<P: AsRef<std::path::Path>>(
                 &mut self,
                 path: P,
             ) -> FlatboxResult<(World, AssetManager, PhysicsHandler)> {
-                use std::fs::File;
-                use std::io::Read;
-
-                let package = File::open(path)?;
-                let decoded = lz4::Decoder::new(package)?;
-                let mut archive = tar::Archive::new(decoded);
-
-                let mut world = None;
-                let mut asset_manager = None;
-                let mut physics_handler = None;
-
-                for file in archive.entries().unwrap() {
-                    let mut file = file.unwrap();
-                    let header = file.header().clone();
-
-                    let mut buffer = vec![];
-                    file.read_to_end(&mut buffer)?;
-                    let mut de = ron::Deserializer::from_bytes(&buffer)
-                        .map_err(|e| ron::Error::from(e))?;
-                    
-                    if header.entry_type() == tar::EntryType::Regular {
-                        match header.path().unwrap().to_str().unwrap() {
-                            "world.ron" => {
-                                world = Some(deserialize_world(self, &mut de)
-                                    .map_err(|e| ron::Error::from(e))?);
-                            },
-                            "assets.ron" => {
-                                asset_manager = Some(AssetManager::deserialize(&mut de)?);
-                            },
-                            "physics.ron" => {
-                                physics_handler = Some(PhysicsHandler::deserialize(&mut de)?);
-                            },
-                            _ => {},
+                let archive = SaveArchive::read(path)?;
+                let _version = GameVersionScope::new(archive.manifest.game_version.as_str());
+
+                let data = archive.get("world.ron")?;
+                #[allow(unused_mut)]
+                let mut world = match SerialFormat::detect(data) {
+                    SerialFormat::Ron => {
+                        let mut de = ron::Deserializer::from_bytes(data)
+                            .map_err(|e| SaveError::invalid("world.ron", e))?;
+
+                        deserialize_world(self, &mut de)
+                            .map_err(|e| SaveError::invalid("world.ron", e))?
+                    },
+                    SerialFormat::Binary => {
+                        let mut de = $crate::assets::rmp_serde::Deserializer::new(&data[BINARY_MAGIC.len()..]);
+
+                        deserialize_world(self, &mut de)
+                            .map_err(|e| SaveError::invalid("world.ron", e))?
+                    },
+                };
+
+                let mut asset_manager = archive.deserialize::<AssetManager>("assets.ron")?;
+                asset_manager.audio = asset_manager.audio.with_requested_backend()?;
+                let physics_handler = archive.deserialize::<PhysicsHandler>("physics.ron")?;
+
+                if archive.contains("entities.ron") {
+                    let entities = archive.deserialize::<Vec<u64>>("entities.ron")?;
+                    let mut entity_map = EntityMap::new();
+
+                    for (i, bits) in entities.into_iter().enumerate() {
+                        if let Some(entity) = Entity::from_bits(bits) {
+                            entity_map.insert(EntityMap::local(i as u32), entity);
                         }
                     }
+
+                    $($(
+                        for (_, mut component) in world.query_mut::<&mut $mapped>() {
+                            MapEntities::map_entities(&mut *component, &entity_map);
+                        }
+                    )+)?
                 }
-                
-                Ok((
-                    world.unwrap(), 
-                    asset_manager.unwrap(), 
-                    physics_handler.unwrap()
-                ))
+
+                Ok((world, asset_manager, physics_handler))
             }
         }
+    };
+}
 
-        fn create_header(path: &'static str, size: usize) -> tar::Header {
-            let mut header = tar::Header::new_gnu();
-            header.set_entry_type(tar::EntryType::Regular);
-            header.set_path(path).unwrap();
-            header.set_size(size as u64);
-            header.set_cksum();
+/// Conversion of the migrated component, which is used by [`impl_save_load!`](crate::impl_save_load)
+#[doc(hidden)]
+#[macro_export]
+macro_rules! save_migration {
+    ($new:ty) => {
+        |old, _: &str| <$new>::from(old)
+    };
+    ($new:ty, $migrate:expr) => {
+        $migrate
+    };
+}
 
-            header
-        }
+/// Game version written by [`impl_save_load!`](crate::impl_save_load), empty when not set
+#[doc(hidden)]
+#[macro_export]
+macro_rules! save_game_version {
+    () => {
+        ""
     };
-}
\ No newline at end of file
+    ($version:expr) => {
+        $version
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use crate::prelude::*;
+    use crate::audio::AudioBackend;
+
+    use super::*;
+
+    #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+    struct OldHealth(u32);
+
+    #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+    struct Health {
+        value: u32,
+        game_version: String,
+    }
+
+    #[derive(Default)]
+    struct OldLoader {
+        components: Vec<String>,
+    }
+
+    #[derive(Default)]
+    struct NewLoader {
+        components: Vec<String>,
+    }
+
+    impl_save_load! {
+        loader: OldLoader,
+        components: [OldHealth],
+        game_version: "0.1.0"
+    }
+
+    impl_save_load! {
+        loader: NewLoader,
+        components: [Health],
+        migrations: [
+            OldHealth => Health: |old: OldHealth, version: &str| Health { value: old.0, game_version: version.to_owned() },
+        ],
+        game_version: "0.2.0"
+    }
+
+    fn archive() -> SaveArchive {
+        let mut archive = SaveArchive::new("1.0.0");
+        archive.insert("world.ron", b"(entities: [])".to_vec());
+        archive.insert("assets.ron", vec![1, 2, 3, 4]);
+        archive
+    }
+
+    fn read_written(archive: &SaveArchive, name: &str) -> FlatboxResult<SaveArchive> {
+        let path: PathBuf = std::env::temp_dir().join(format!("flatbox-{name}-{}.sav", std::process::id()));
+        archive.write(&path)?;
+
+        let result = SaveArchive::read(&path);
+        std::fs::remove_file(&path)?;
+        result
+    }
+
+    #[test]
+    fn manifest_round_trip() {
+        let read = read_written(&archive(), "round-trip").unwrap();
+
+        assert_eq!(read.manifest, archive().manifest);
+        assert_eq!(read.get("assets.ron").unwrap(), &[1, 2, 3, 4]);
+        assert_eq!(read.manifest.get("assets.ron").unwrap().crc32, crc32fast::hash(&[1, 2, 3, 4]));
+    }
+
+    #[test]
+    fn checksum_mismatch() {
+        let mut archive = archive();
+        archive.manifest.entries[1].crc32 ^= 1;
+
+        let result = read_written(&archive, "checksum");
+        assert!(matches!(
+            result,
+            Err(crate::Result::SaveError(SaveError::ChecksumMismatch { ref entry, .. })) if entry == "assets.ron"
+        ));
+    }
+
+    #[test]
+    fn migrations_receive_game_version() {
+        let path = std::env::temp_dir().join(format!("flatbox-migration-{}.sav", std::process::id()));
+        let asset_manager = AssetManager::with_audio_backend(1, 1, AudioBackend::None);
+
+        let mut world = World::new();
+        world.spawn((OldHealth(42),));
+
+        OldLoader::default().save_as(&world, &asset_manager, &PhysicsHandler::new(), &path, SerialFormat::Binary).unwrap();
+        let (mut world, _, _) = NewLoader::default().load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let health = world.query_mut::<&Health>().into_iter().map(|(_, h)| h.clone()).collect::<Vec<_>>();
+        assert_eq!(health, vec![Health { value: 42, game_version: String::from("0.1.0") }]);
+    }
+
+    #[test]
+    fn size_mismatch() {
+        let mut archive = archive();
+        archive.manifest.entries[1].size += 1;
+
+        let result = read_written(&archive, "size");
+        assert!(matches!(
+            result,
+            Err(crate::Result::SaveError(SaveError::SizeMismatch { ref entry, expected: 5, actual: 4 })) if entry == "assets.ron"
+        ));
+    }
+}
@@ -0,0 +1,447 @@
+use std::fmt;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use serde::{Serialize, Deserialize};
+use ron::ser::PrettyConfig;
+
+use crate::ecs::World;
+use crate::error::FlatboxResult;
+use crate::assets::asset_manager::AssetManager;
+use crate::assets::save_load::SaveLoad;
+use crate::assets::format::SerialFormat;
+use crate::physics::physics_handler::PhysicsHandler;
+#[cfg(feature = "render")]
+use crate::render::{Renderer, ScreenshotExt};
+
+/// Default count of the rotated autosave slots
+pub const DEFAULT_AUTOSAVES: u32 = 3;
+
+/// Width of the slot thumbnails in pixels
+pub const THUMBNAIL_WIDTH: u32 = 320;
+
+const SAVE_FILE: &str = "save.sav";
+const METADATA_FILE: &str = "slot.ron";
+const THUMBNAIL_FILE: &str = "thumbnail.png";
+
+/// Suffix of the slot directory, which is being written
+const TEMP_SUFFIX: &str = ".tmp";
+/// Suffix of the replaced slot directory, which is removed after the new one is in place
+const OLD_SUFFIX: &str = ".old";
+
+/// Identifier of the save slot
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum SaveSlot {
+    Numbered(u32),
+    Named(String),
+    Autosave(u32),
+}
+
+impl SaveSlot {
+    /// Name of the slot directory. Characters of the names, which aren't alphanumeric,
+    /// are escaped as `_` with the hex bytes, so different names never share a directory
+    fn file_stem(&self) -> String {
+        match self {
+            SaveSlot::Numbered(index) => format!("slot-{index}"),
+            SaveSlot::Autosave(index) => format!("autosave-{index}"),
+            SaveSlot::Named(name) => {
+                let mut stem = String::from("named-");
+
+                for c in name.chars() {
+                    if c.is_alphanumeric() || c == '-' {
+                        stem.push(c);
+                    } else {
+                        for byte in c.to_string().bytes() {
+                            stem.push_str(&format!("_{byte:02x}"));
+                        }
+                    }
+                }
+
+                stem
+            },
+        }
+    }
+}
+
+impl fmt::Display for SaveSlot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SaveSlot::Numbered(index) => write!(f, "Slot {index}"),
+            SaveSlot::Named(name) => write!(f, "{name}"),
+            SaveSlot::Autosave(index) => write!(f, "Autosave {index}"),
+        }
+    }
+}
+
+/// Information about the saved game, which is stored next to the save
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SlotMetadata {
+    pub slot: SaveSlot,
+    /// Time of saving
+    pub timestamp: SystemTime,
+    /// Total play time at the moment of saving
+    pub play_time: Duration,
+    /// Name of the level, where the game has been saved
+    pub level: String,
+}
+
+/// Manager of the save slots in the user data directory. Every slot is a directory with
+/// the save archive, [`SlotMetadata`] and optional thumbnail. The slot is written to the
+/// temporary directory first, which then replaces the previous one, so the previous save
+/// survives a crash
+///
+/// # Usage example
+///
+/// ```rust
+/// let slots = SaveSlots::for_game("my_game")?;
+///
+/// slots.save(&SaveSlot::Numbered(1), &mut MySaveLoader::default(), &world, &asset_manager, &physics_handler, play_time, "Forest")?;
+/// slots.save_thumbnail(&SaveSlot::Numbered(1), &mut renderer)?;
+///
+/// for metadata in slots.list() {
+///     println!("{}: {} ({:?})", metadata.slot, metadata.level, metadata.play_time);
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct SaveSlots {
+    directory: PathBuf,
+    /// Count of the rotated autosave slots
+    pub autosaves: u32,
+    /// Format of the written saves
+    pub format: SerialFormat,
+}
+
+impl SaveSlots {
+    /// Use `directory` for the saves. It's created if missing. Slots, which have been
+    /// interrupted while saving, are restored
+    pub fn new<P: Into<PathBuf>>(directory: P) -> FlatboxResult<Self> {
+        let directory = directory.into();
+        std::fs::create_dir_all(&directory)?;
+        recover_slots(&directory)?;
+
+        Ok(SaveSlots {
+            directory,
+            autosaves: DEFAULT_AUTOSAVES,
+            format: SerialFormat::Ron,
+        })
+    }
+
+    /// Use `saves` subdirectory of the game's user data directory
+    pub fn for_game(game: &str) -> FlatboxResult<Self> {
+        let directory = user_data_dir(game)
+            .ok_or(crate::Result::yell("Cannot find user data directory"))?;
+
+        SaveSlots::new(directory.join("saves"))
+    }
+
+    pub fn directory(&self) -> &Path {
+        &self.directory
+    }
+
+    /// Directory with the slot files
+    pub fn slot_path(&self, slot: &SaveSlot) -> PathBuf {
+        self.directory.join(slot.file_stem())
+    }
+
+    pub fn save_path(&self, slot: &SaveSlot) -> PathBuf {
+        self.slot_path(slot).join(SAVE_FILE)
+    }
+
+    pub fn metadata_path(&self, slot: &SaveSlot) -> PathBuf {
+        self.slot_path(slot).join(METADATA_FILE)
+    }
+
+    pub fn thumbnail_path(&self, slot: &SaveSlot) -> PathBuf {
+        self.slot_path(slot).join(THUMBNAIL_FILE)
+    }
+
+    pub fn exists(&self, slot: &SaveSlot) -> bool {
+        self.save_path(slot).is_file() && self.metadata_path(slot).is_file()
+    }
+
+    /// Save game to the slot, replacing the previous save along with its thumbnail
+    #[allow(clippy::too_many_arguments)]
+    pub fn save<L: SaveLoad, S: Into<String>>(
+        &self,
+        slot: &SaveSlot,
+        loader: &mut L,
+        world: &World,
+        asset_manager: &AssetManager,
+        physics_handler: &PhysicsHandler,
+        play_time: Duration,
+        level: S,
+    ) -> FlatboxResult<SlotMetadata> {
+        let path = self.slot_path(slot);
+        let temp = with_suffix(&path, TEMP_SUFFIX);
+
+        if temp.exists() {
+            std::fs::remove_dir_all(&temp)?;
+        }
+        std::fs::create_dir_all(&temp)?;
+
+        let metadata = SlotMetadata {
+            slot: slot.clone(),
+            timestamp: SystemTime::now(),
+            play_time,
+            level: level.into(),
+        };
+
+        let written = write_synced(&temp.join(SAVE_FILE), |path| {
+            loader.save_as(world, asset_manager, physics_handler, path, self.format)
+        }).and_then(|_| write_synced(&temp.join(METADATA_FILE), |path| {
+            let data = ron::ser::to_string_pretty(&metadata, PrettyConfig::default())?;
+            std::fs::write(path, data)?;
+            Ok(())
+        }));
+
+        if let Err(error) = written {
+            let _ = std::fs::remove_dir_all(&temp);
+            return Err(error);
+        }
+
+        replace_dir(&temp, &path)?;
+
+        Ok(metadata)
+    }
+
+    /// Save game to the next autosave slot. The oldest autosave is replaced,
+    /// when all [`autosaves`](#structfield.autosaves) slots are used
+    pub fn autosave<L: SaveLoad, S: Into<String>>(
+        &self,
+        loader: &mut L,
+        world: &World,
+        asset_manager: &AssetManager,
+        physics_handler: &PhysicsHandler,
+        play_time: Duration,
+        level: S,
+    ) -> FlatboxResult<SlotMetadata> {
+        let slot = (0..self.autosaves.max(1))
+            .map(SaveSlot::Autosave)
+            .min_by_key(|slot| self.metadata(slot).map(|m| m.timestamp))
+            .unwrap();
+
+        self.save(&slot, loader, world, asset_manager, physics_handler, play_time, level)
+    }
+
+    /// Capture the current frame as the thumbnail of the saved slot
+    #[cfg(feature = "render")]
+    pub fn save_thumbnail(&self, slot: &SaveSlot, renderer: &mut Renderer) -> FlatboxResult<()> {
+        let screen = renderer.capture()?;
+        let height = (THUMBNAIL_WIDTH * screen.height() / screen.width().max(1)).max(1);
+        let thumbnail = image::imageops::thumbnail(&screen, THUMBNAIL_WIDTH, height);
+
+        write_atomic(self.thumbnail_path(slot), |path| {
+            thumbnail.save_with_format(path, image::ImageFormat::Png)?;
+            Ok(())
+        })
+    }
+
+    /// Load game from the slot
+    pub fn load<L: SaveLoad>(
+        &self,
+        slot: &SaveSlot,
+        loader: &mut L,
+    ) -> FlatboxResult<(World, AssetManager, PhysicsHandler)> {
+        loader.load(self.save_path(slot))
+    }
+
+    /// Read metadata of the slot. Returns `None` if the slot is empty or invalid
+    pub fn metadata(&self, slot: &SaveSlot) -> Option<SlotMetadata> {
+        read_metadata(&self.metadata_path(slot))
+    }
+
+    /// Path of the slot thumbnail, if it exists
+    pub fn thumbnail(&self, slot: &SaveSlot) -> Option<PathBuf> {
+        let path = self.thumbnail_path(slot);
+        path.is_file().then_some(path)
+    }
+
+    /// Metadata of the existing slots, sorted from the latest to the oldest
+    pub fn list(&self) -> Vec<SlotMetadata> {
+        let entries = match std::fs::read_dir(&self.directory) {
+            Ok(entries) => entries,
+            Err(_) => return vec![],
+        };
+
+        let mut slots = entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.is_dir())
+            .filter_map(|path| {
+                let metadata = read_metadata(&path.join(METADATA_FILE))?;
+                (self.slot_path(&metadata.slot) == path).then_some(metadata)
+            })
+            .filter(|metadata| self.save_path(&metadata.slot).is_file())
+            .collect::<Vec<_>>();
+
+        slots.sort_by_key(|metadata| std::cmp::Reverse(metadata.timestamp));
+        slots
+    }
+
+    /// Remove all files of the slot
+    pub fn remove(&self, slot: &SaveSlot) -> FlatboxResult<()> {
+        let path = self.slot_path(slot);
+
+        if path.exists() {
+            std::fs::remove_dir_all(path)?;
+        }
+
+        Ok(())
+    }
+}
+
+fn read_metadata(path: &Path) -> Option<SlotMetadata> {
+    let data = std::fs::read_to_string(path).ok()?;
+
+    match ron::from_str(&data) {
+        Ok(metadata) => Some(metadata),
+        Err(error) => {
+            log::warn!("Invalid save slot metadata '{}': {error}", path.display());
+            None
+        },
+    }
+}
+
+/// Write the file and flush it to the disk
+fn write_synced<F>(path: &Path, write: F) -> FlatboxResult<()>
+where
+    F: FnOnce(&Path) -> FlatboxResult<()>,
+{
+    write(path)?;
+    File::open(path)?.sync_all()?;
+
+    Ok(())
+}
+
+/// Write the file through temporary one, which replaces `path` only after it's completely written
+#[cfg(feature = "render")]
+fn write_atomic<P, F>(path: P, write: F) -> FlatboxResult<()>
+where
+    P: AsRef<Path>,
+    F: FnOnce(&Path) -> FlatboxResult<()>,
+{
+    let path = path.as_ref();
+    let temp = with_suffix(path, TEMP_SUFFIX);
+
+    if let Err(error) = write_synced(&temp, write) {
+        let _ = std::fs::remove_file(&temp);
+        return Err(error);
+    }
+
+    std::fs::rename(&temp, path)?;
+
+    Ok(())
+}
+
+/// Replace the `path` directory with the completely written `temp` one. Directories can't be
+/// replaced by renaming, so the previous one is moved aside until the new one is in place
+fn replace_dir(temp: &Path, path: &Path) -> FlatboxResult<()> {
+    let old = with_suffix(path, OLD_SUFFIX);
+
+    if old.exists() {
+        std::fs::remove_dir_all(&old)?;
+    }
+
+    if path.exists() {
+        std::fs::rename(path, &old)?;
+    }
+
+    std::fs::rename(temp, path)?;
+
+    if let Err(error) = std::fs::remove_dir_all(&old) {
+        if error.kind() != std::io::ErrorKind::NotFound {
+            log::warn!("Cannot remove replaced save slot '{}': {error}", old.display());
+        }
+    }
+
+    Ok(())
+}
+
+/// Finish replacing of the slot directories, which has been interrupted by a crash.
+/// Partially written slots are removed, and replaced ones are restored, if the new one is missing
+fn recover_slots(directory: &Path) -> FlatboxResult<()> {
+    for entry in std::fs::read_dir(directory)? {
+        let path = entry?.path();
+        let name = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+
+        if !path.is_dir() {
+            continue;
+        }
+
+        if name.ends_with(TEMP_SUFFIX) {
+            std::fs::remove_dir_all(&path)?;
+        } else if let Some(stem) = name.strip_suffix(OLD_SUFFIX) {
+            let slot = directory.join(stem);
+
+            if slot.exists() {
+                std::fs::remove_dir_all(&path)?;
+            } else {
+                log::warn!("Restoring save slot '{stem}', which has been interrupted while saving");
+                std::fs::rename(&path, slot)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut path = path.as_os_str().to_owned();
+    path.push(suffix);
+    PathBuf::from(path)
+}
+
+/// Platform-specific directory for the user data of the game, e.g.
+/// `~/.local/share/<game>` on Linux or `%APPDATA%\<game>` on Windows
+pub fn user_data_dir(game: &str) -> Option<PathBuf> {
+    let env = |name| std::env::var_os(name).filter(|v| !v.is_empty()).map(PathBuf::from);
+
+    #[cfg(target_os = "windows")]
+    let base = env("APPDATA");
+
+    #[cfg(target_os = "macos")]
+    let base = env("HOME").map(|home| home.join("Library/Application Support"));
+
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    let base = env("XDG_DATA_HOME").or_else(|| env("HOME").map(|home| home.join(".local/share")));
+
+    base.map(|base| base.join(game))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn named_slots_dont_collide() {
+        let names = ["a/b", "a_b", "a b", "a_2fb", "a-b", "ab"];
+        let stems = names.iter()
+            .map(|name| SaveSlot::Named(name.to_string()).file_stem())
+            .collect::<std::collections::HashSet<_>>();
+
+        assert_eq!(stems.len(), names.len());
+        assert!(stems.iter().all(|stem| !stem.contains(['/', '\\', '.', ' '])));
+    }
+
+    #[test]
+    fn interrupted_slots_are_recovered() {
+        let directory = std::env::temp_dir().join(format!("flatbox-slots-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&directory);
+
+        std::fs::create_dir_all(directory.join("slot-1.old")).unwrap();
+        std::fs::create_dir_all(directory.join("slot-1.tmp")).unwrap();
+        std::fs::create_dir_all(directory.join("slot-2")).unwrap();
+        std::fs::create_dir_all(directory.join("slot-2.old")).unwrap();
+
+        let slots = SaveSlots::new(&directory).unwrap();
+
+        assert!(slots.slot_path(&SaveSlot::Numbered(1)).is_dir());
+        assert!(slots.slot_path(&SaveSlot::Numbered(2)).is_dir());
+        assert!(!directory.join("slot-1.old").exists());
+        assert!(!directory.join("slot-1.tmp").exists());
+        assert!(!directory.join("slot-2.old").exists());
+
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+}
@@ -1,8 +1,5 @@
 use std::sync::Arc;
 use std::path::Path;
-use std::fs::{File, read_to_string};
-
-use ron::ser::{Serializer, PrettyConfig};
 
 use serde::{
     Serialize, 
@@ -16,7 +13,10 @@ use crate::error::*;
 use crate::assets::{
     asset_manager::*,
     ser_component::*,
+    mapping::AssetMapping,
+    format::SerialFormat,
 };
+use crate::math::transform::Transform;
 
 #[derive(Default, Serialize, Deserialize)]
 #[serde(rename = "Entity")]
@@ -63,22 +63,64 @@ impl Scene {
         Scene::default()
     }
     
+    /// Load scene of any [`SerialFormat`]
     pub fn load<P: AsRef<Path>>(path: P) -> FlatboxResult<Self> {     
-        Ok(ron::from_str::<Scene>(
-            &read_to_string(path)?
-        )?)
+        SerialFormat::load(path)
     }
     
     pub fn save<P: AsRef<std::path::Path>>(&self, path: P) -> FlatboxResult<()> {     
-        let buf = File::create(path)?;                    
-        let mut ser = Serializer::new(buf, Some(
-            PrettyConfig::new()
-                .struct_names(true)
-        ))?;   
-        
-        self.serialize(&mut ser)?;
-                        
-        Ok(())
+        self.save_as(path, SerialFormat::Ron)
+    }
+
+    pub fn save_as<P: AsRef<Path>>(&self, path: P, format: SerialFormat) -> FlatboxResult<()> {
+        format.save(self, path)
+    }
+
+    /// Convert scene file to the `format`, e.g. to ship scenes, authored in RON, as binary ones
+    pub fn convert<P: AsRef<Path>, Q: AsRef<Path>>(input: P, output: Q, format: SerialFormat) -> FlatboxResult<()> {
+        format.convert::<Scene, _, _>(input, output)
+    }
+
+    /// Snapshot `entities` of the `world` with their components, registered in the `registry`,
+    /// and copy of the assets. References between the captured entities are stored as the scene-local ones,
+    /// references to the other entities are stored as [`EntityMap::invalid`]
+    pub fn capture<I: IntoIterator<Item = Entity>>(
+        world: &World,
+        entities: I,
+        asset_manager: &AssetManager,
+        registry: &ComponentRegistry,
+    ) -> FlatboxResult<Self> {
+        let entities = entities.into_iter().collect::<Vec<_>>();
+        let mut entity_map = EntityMap::strict();
+
+        for (i, &entity) in entities.iter().enumerate() {
+            entity_map.insert(entity, EntityMap::local(i as u32));
+        }
+
+        let mut scene = Scene {
+            assets: asset_manager.snapshot()?,
+            ..Default::default()
+        };
+
+        for entity in entities {
+            let entity = world.entity(entity)
+                .map_err(|_| crate::Result::yell(format!("Cannot capture missing entity {entity:?}")))?;
+
+            scene.entities.push(SerializableEntity {
+                components: registry.extract(&entity, &entity_map),
+            });
+        }
+
+        Ok(scene)
+    }
+
+    /// Snapshot the whole `world`. See [`capture`](#method.capture)
+    pub fn capture_world(
+        world: &World,
+        asset_manager: &AssetManager,
+        registry: &ComponentRegistry,
+    ) -> FlatboxResult<Self> {
+        Scene::capture(world, world.iter().map(|e| e.entity()), asset_manager, registry)
     }
 }
 
@@ -133,24 +175,126 @@ macro_rules! scene {
     };
 }
 
+/// [`Scene`], which assets have been merged into the game [`AssetManager`],
+/// so it can be spawned many times with [`SpawnSceneExt::spawn_instance`]
+pub struct PreparedScene {
+    pub entities: Vec<SerializableEntity>,
+    /// Mapping of the scene's asset handles to the merged ones
+    pub mapping: AssetMapping,
+}
+
+impl Scene {
+    /// Merge scene assets into the `asset_manager`
+    pub fn prepare(self, asset_manager: &mut AssetManager) -> PreparedScene {
+        let mapping = asset_manager.merge(self.assets);
+
+        PreparedScene {
+            entities: self.entities,
+            mapping,
+        }
+    }
+}
+
+impl PreparedScene {
+    fn build_entity(&self, entity: &SerializableEntity, entity_map: &EntityMap) -> EntityBuilder {
+        let mut entity_builder = EntityBuilder::new();
+
+        for component in &entity.components {
+            component.add_into_mapped(&mut entity_builder, entity_map);
+        }
+
+        for component in &entity.components {
+            component.map_assets(&mut entity_builder, &self.mapping);
+        }
+
+        entity_builder
+    }
+}
+
 pub trait SpawnSceneExt {
+    /// Replace the whole world and asset manager with the scene
     fn spawn_scene(&mut self, scene: Scene, asset_manager: &mut AssetManager);
+
+    /// Spawn scene along with existing entities, merging its assets into the `asset_manager`.
+    /// Returns spawned entities
+    fn spawn_scene_additive(&mut self, scene: Scene, asset_manager: &mut AssetManager) -> Vec<Entity>;
+
+    /// Spawn instance of the prepared scene. If `parent` is set, transforms of the
+    /// spawned entities are treated as local to it. Returns spawned entities
+    fn spawn_instance(&mut self, scene: &PreparedScene, parent: Option<&Transform>) -> Vec<Entity>;
+
+    /// Insert components of the prepared scene into the `entities` of its spawned instance,
+    /// replacing the ones of the same type. Despawned entities are skipped
+    fn update_instance(&mut self, scene: &PreparedScene, entities: &[Entity]);
 }
 
 impl SpawnSceneExt for World {
+    fn spawn_scene_additive(&mut self, scene: Scene, asset_manager: &mut AssetManager) -> Vec<Entity> {
+        let scene = scene.prepare(asset_manager);
+        self.spawn_instance(&scene, None)
+    }
+
+    fn spawn_instance(&mut self, scene: &PreparedScene, parent: Option<&Transform>) -> Vec<Entity> {
+        let spawned = self.reserve_entities(scene.entities.len() as u32).collect::<Vec<_>>();
+        let entity_map = local_entity_map(&spawned);
+
+        for (entity, &handle) in scene.entities.iter().zip(&spawned) {
+            let mut entity_builder = scene.build_entity(entity, &entity_map);
+
+            if let (Some(parent), Some(transform)) = (parent, entity_builder.get_mut::<&mut Transform>()) {
+                *transform = parent.combine(transform);
+            }
+
+            self.insert(handle, entity_builder.build()).expect("Reserved entity is missing");
+        }
+
+        spawned
+    }
+
+    fn update_instance(&mut self, scene: &PreparedScene, entities: &[Entity]) {
+        let entity_map = local_entity_map(entities);
+
+        for (entity, &handle) in scene.entities.iter().zip(entities) {
+            if self.contains(handle) {
+                let mut entity_builder = scene.build_entity(entity, &entity_map);
+                self.insert(handle, entity_builder.build()).expect("Entity is missing");
+            }
+        }
+    }
+
     fn spawn_scene(&mut self, scene: Scene, asset_manager: &mut AssetManager) {
         self.clear();
 
-        for entity in scene.entities {
+        let spawned = self.reserve_entities(scene.entities.len() as u32).collect::<Vec<_>>();
+        let entity_map = local_entity_map(&spawned);
+
+        for (entity, handle) in scene.entities.into_iter().zip(spawned) {
             let mut entity_builder = EntityBuilder::new();
             
             for component in entity.components {
-                component.add_into(&mut entity_builder);
+                component.add_into_mapped(&mut entity_builder, &entity_map);
             }
             
-            self.spawn(entity_builder.build());
+            self.insert(handle, entity_builder.build()).expect("Reserved entity is missing");
         }
         
         *asset_manager = scene.assets;
+
+        // Scene assets don't open the audio device
+        match asset_manager.audio.with_requested_backend() {
+            Ok(audio) => asset_manager.audio = audio,
+            Err(error) => log::error!("Cannot open audio backend of the scene: {error}"),
+        }
     }
-}
\ No newline at end of file
+}
+
+/// Map scene-local entity references to the spawned entities
+fn local_entity_map(spawned: &[Entity]) -> EntityMap {
+    let mut entity_map = EntityMap::new();
+
+    for (i, &entity) in spawned.iter().enumerate() {
+        entity_map.insert(EntityMap::local(i as u32), entity);
+    }
+
+    entity_map
+}
@@ -1,8 +1,23 @@
+use std::any::TypeId;
+use std::sync::Arc;
+
+use hecs::EntityRef;
+
 use crate::prelude::*;
 
 #[typetag::serde(tag = "component")]
 pub trait SerializableComponent: Component {
     fn add_into(&self, entity_builder: &mut EntityBuilder);
+
+    /// Add component, which entity references are rewritten with the `entity_map`.
+    /// Implemented by `impl_ser_component!(map_entities: ...)` for [`MapEntities`] components
+    fn add_into_mapped(&self, entity_builder: &mut EntityBuilder, _entity_map: &EntityMap) {
+        self.add_into(entity_builder);
+    }
+
+    /// Remap asset handles of the component, which has been added into the `entity_builder`.
+    /// Implemented by `impl_ser_component!(map_assets: ...)` for [`MapAssets`] components
+    fn map_assets(&self, _entity_builder: &mut EntityBuilder, _mapping: &AssetMapping) {}
 }
 
 /// Macro for implementing [`SerializableComponent`] trait for multiple types, that implement [`Clone`] trait; for using in [`Scene`]'s. Use to avoid boilerplate
@@ -23,8 +38,54 @@ pub trait SerializableComponent: Component {
 /// 
 /// ```
 /// 
+/// Components, which store entity references, should implement [`MapEntities`]
+/// and be listed after `map_entities:`
+/// 
+/// ```rust
+/// impl_ser_component!(map_entities: Target, Owner);
+/// ```
+/// 
+/// Components, which store asset handles, should implement [`MapAssets`]
+/// and be listed after `map_assets:`
+/// 
+/// ```rust
+/// impl_ser_component!(map_assets: Portrait, Handle<Image>);
+/// ```
+/// 
 #[macro_export]
 macro_rules! impl_ser_component {
+    (map_entities: $($comp:ty),+) => {
+        $(
+            #[typetag::serde]
+            impl SerializableComponent for $comp {
+                fn add_into(&self, entity_builder: &mut EntityBuilder) {
+                    entity_builder.add(self.clone());
+                }
+
+                fn add_into_mapped(&self, entity_builder: &mut EntityBuilder, entity_map: &EntityMap) {
+                    let mut component = self.clone();
+                    MapEntities::map_entities(&mut component, entity_map);
+                    entity_builder.add(component);
+                }
+            }
+        )+
+    };
+    (map_assets: $($comp:ty),+) => {
+        $(
+            #[typetag::serde]
+            impl SerializableComponent for $comp {
+                fn add_into(&self, entity_builder: &mut EntityBuilder) {
+                    entity_builder.add(self.clone());
+                }
+
+                fn map_assets(&self, entity_builder: &mut EntityBuilder, mapping: &AssetMapping) {
+                    if let Some(component) = entity_builder.get_mut::<&mut $comp>() {
+                        MapAssets::map_assets(component, mapping);
+                    }
+                }
+            }
+        )+
+    };
     ($($comp:ty),+) => {
         $(
             #[typetag::serde]
@@ -34,16 +95,100 @@ macro_rules! impl_ser_component {
                 }
             }
         )+
-    }
+    };
 }
 
 impl_ser_component!(
     bool, u8, i8, u16, i16, u32, i32, u64, i64, usize, isize,
-    BodyHandle, Timer, Transform, AssetHandle<'S'>
+    BodyHandle, Timer, Transform,
+    AudioCast, AudioListener, AudioOccluder
 );
 
+impl_ser_component!(map_assets: AssetHandle<'S'>, AssetHandle<'L'>, AudioStorage);
+
 #[cfg(feature = "render")]
 impl_ser_component!(
-    Camera, DirectionalLight, Model, PointLight, 
-    AssetHandle<'T'>, AssetHandle<'M'>
+    Camera, DirectionalLight, Model, PointLight
 );
+
+#[cfg(feature = "render")]
+impl_ser_component!(map_assets: AssetHandle<'T'>, AssetHandle<'M'>);
+
+type ComponentExtractor = Box<dyn Fn(&EntityRef, &EntityMap) -> Option<Arc<dyn SerializableComponent>> + Send + Sync>;
+
+/// Collection of [`SerializableComponent`]s, which are copied from the [`World`] during
+/// [`Scene::capture`]. Components, which aren't registered, are skipped
+pub struct ComponentRegistry {
+    extractors: Vec<(TypeId, ComponentExtractor)>,
+}
+
+impl ComponentRegistry {
+    /// Create empty registry. Use [`Default`] to create one with engine components registered
+    pub fn new() -> Self {
+        ComponentRegistry { extractors: vec![] }
+    }
+
+    /// Register component. Registering the same type again has no effect
+    pub fn register<T: SerializableComponent + Clone>(&mut self) -> &mut Self {
+        self.add::<T>(Box::new(|entity, _| {
+            entity.get::<&T>().map(|c| Arc::new((*c).clone()) as Arc<dyn SerializableComponent>)
+        }))
+    }
+
+    /// Register component with entity references. They are rewritten to the scene-local ones
+    pub fn register_mapped<T: SerializableComponent + MapEntities + Clone>(&mut self) -> &mut Self {
+        self.add::<T>(Box::new(|entity, entity_map| {
+            entity.get::<&T>().map(|c| {
+                let mut component = (*c).clone();
+                component.map_entities(entity_map);
+                Arc::new(component) as Arc<dyn SerializableComponent>
+            })
+        }))
+    }
+
+    pub fn contains<T: Component>(&self) -> bool {
+        self.extractors.iter().any(|(id, _)| *id == TypeId::of::<T>())
+    }
+
+    /// Copy registered components of the entity
+    pub fn extract(&self, entity: &EntityRef, entity_map: &EntityMap) -> Vec<Arc<dyn SerializableComponent>> {
+        self.extractors.iter()
+            .filter_map(|(_, extract)| extract(entity, entity_map))
+            .collect()
+    }
+
+    fn add<T: Component>(&mut self, extractor: ComponentExtractor) -> &mut Self {
+        if !self.contains::<T>() {
+            self.extractors.push((TypeId::of::<T>(), extractor));
+        }
+
+        self
+    }
+}
+
+impl Default for ComponentRegistry {
+    fn default() -> Self {
+        let mut registry = ComponentRegistry::new();
+        registry
+            .register::<BodyHandle>()
+            .register::<Timer>()
+            .register::<Transform>()
+            .register::<AssetHandle<'S'>>()
+            .register::<AssetHandle<'L'>>()
+            .register::<AudioCast>()
+            .register::<AudioListener>()
+            .register::<AudioStorage>()
+            .register::<AudioOccluder>();
+
+        #[cfg(feature = "render")]
+        registry
+            .register::<Camera>()
+            .register::<DirectionalLight>()
+            .register::<Model>()
+            .register::<PointLight>()
+            .register::<AssetHandle<'T'>>()
+            .register::<AssetHandle<'M'>>();
+
+        registry
+    }
+}
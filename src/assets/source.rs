@@ -0,0 +1,348 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::SystemTime;
+
+use parking_lot::{RwLock, const_rwlock};
+
+use crate::error::FlatboxResult;
+
+/// Storage of asset files, which can be mounted to the [`Vfs`]. Paths, passed
+/// to the source, are relative to its mount point
+pub trait AssetSource: Send + Sync + 'static {
+    /// Read whole file content
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>>;
+
+    /// Check whether the source contains file
+    fn exists(&self, path: &Path) -> bool;
+
+    /// Get modification time of the file. It's used by [`AssetWatcher`](super::AssetWatcher)
+    fn modified(&self, _path: &Path) -> Option<SystemTime> {
+        None
+    }
+}
+
+/// Source, which reads files from the directory on disk
+#[derive(Debug, Clone)]
+pub struct DirectorySource {
+    root: PathBuf,
+}
+
+impl DirectorySource {
+    pub fn new<P: Into<PathBuf>>(root: P) -> Self {
+        DirectorySource { root: root.into() }
+    }
+
+    /// Get path of the file on disk. Absolute paths and paths with `..` are rejected,
+    /// so files outside of the root can't be read
+    fn file_path(&self, path: &Path) -> io::Result<PathBuf> {
+        if path.components().any(|c| !matches!(c, Component::Normal(_) | Component::CurDir)) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Path '{}' is outside of the source directory", path.display()),
+            ));
+        }
+
+        Ok(self.root.join(path))
+    }
+}
+
+impl AssetSource for DirectorySource {
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        std::fs::read(self.file_path(path)?)
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        self.file_path(path).is_ok_and(|path| path.is_file())
+    }
+
+    fn modified(&self, path: &Path) -> Option<SystemTime> {
+        std::fs::metadata(self.file_path(path).ok()?).and_then(|m| m.modified()).ok()
+    }
+}
+
+/// Source, which contains files of the `.tar.lz4` archive. The archive is unpacked to memory on opening
+#[derive(Debug, Clone, Default)]
+pub struct ArchiveSource {
+    files: HashMap<PathBuf, Arc<[u8]>>,
+}
+
+impl ArchiveSource {
+    /// Open `.tar.lz4` archive from disk
+    pub fn open<P: AsRef<Path>>(path: P) -> FlatboxResult<Self> {
+        ArchiveSource::from_reader(File::open(path)?)
+    }
+
+    /// Read `.tar.lz4` archive from any reader
+    pub fn from_reader<R: Read>(reader: R) -> FlatboxResult<Self> {
+        let decoded = lz4::Decoder::new(reader)?;
+        let mut archive = tar::Archive::new(decoded);
+        let mut files = HashMap::new();
+
+        for entry in archive.entries()? {
+            let mut entry = entry?;
+            if entry.header().entry_type() != tar::EntryType::Regular {
+                continue;
+            }
+
+            let path = normalize(&entry.path()?);
+            let mut buffer = vec![];
+            entry.read_to_end(&mut buffer)?;
+
+            files.insert(path, Arc::from(buffer));
+        }
+
+        Ok(ArchiveSource { files })
+    }
+
+    /// Paths of the archived files
+    pub fn paths(&self) -> impl Iterator<Item = &Path> {
+        self.files.keys().map(|p| p.as_path())
+    }
+}
+
+impl AssetSource for ArchiveSource {
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        self.files
+            .get(&normalize(path))
+            .map(|data| data.to_vec())
+            .ok_or_else(|| not_found(path))
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        self.files.contains_key(&normalize(path))
+    }
+}
+
+/// Content of the in-memory file along with its modification time
+type MemoryFile = (Arc<[u8]>, SystemTime);
+
+/// Source, which keeps files in memory. Files can be added after mounting
+#[derive(Debug, Default)]
+pub struct MemorySource {
+    files: RwLock<HashMap<PathBuf, MemoryFile>>,
+}
+
+impl MemorySource {
+    pub fn new() -> Self {
+        MemorySource::default()
+    }
+
+    /// Add file to the source
+    pub fn with_file<P: AsRef<Path>>(self, path: P, data: impl Into<Vec<u8>>) -> Self {
+        self.insert(path, data);
+        self
+    }
+
+    /// Add or replace file. Replaced file is reported as modified
+    pub fn insert<P: AsRef<Path>>(&self, path: P, data: impl Into<Vec<u8>>) {
+        self.files.write().insert(
+            normalize(path.as_ref()),
+            (Arc::from(data.into()), SystemTime::now()),
+        );
+    }
+
+    pub fn remove<P: AsRef<Path>>(&self, path: P) -> bool {
+        self.files.write().remove(&normalize(path.as_ref())).is_some()
+    }
+}
+
+impl AssetSource for MemorySource {
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        self.files
+            .read()
+            .get(&normalize(path))
+            .map(|(data, _)| data.to_vec())
+            .ok_or_else(|| not_found(path))
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        self.files.read().contains_key(&normalize(path))
+    }
+
+    fn modified(&self, path: &Path) -> Option<SystemTime> {
+        self.files.read().get(&normalize(path)).map(|(_, time)| *time)
+    }
+}
+
+struct Mount {
+    id: u64,
+    point: PathBuf,
+    priority: i32,
+    source: Arc<dyn AssetSource>,
+}
+
+static MOUNTS: RwLock<Vec<Mount>> = const_rwlock(Vec::new());
+static NEXT_MOUNT_ID: AtomicU64 = AtomicU64::new(0);
+
+/// Source, which is mounted until the guard is dropped. It's returned by [`Vfs::mount_scoped`]
+#[must_use = "the source is unmounted when the guard is dropped"]
+pub struct MountGuard {
+    id: u64,
+}
+
+impl Drop for MountGuard {
+    fn drop(&mut self) {
+        MOUNTS.write().retain(|m| m.id != self.id);
+    }
+}
+
+/// Global virtual filesystem, which all engine loaders read assets through.
+/// Paths are resolved through mounted [`AssetSource`]s in priority order.
+/// If no source contains the file, it's read from disk as is
+///
+/// # Usage example
+///
+/// ```rust
+/// // Files from `data.tar.lz4` are available as `assets/...`
+/// Vfs::mount("assets", ArchiveSource::open("data.tar.lz4")?, 0);
+/// // Loose files override archived ones
+/// Vfs::mount("assets", DirectorySource::new("mods/assets"), 10);
+///
+/// let handle = asset_manager.create_texture("assets/textures/grass.png", Filter::Linear);
+/// ```
+pub struct Vfs;
+
+impl Vfs {
+    /// Mount source to the `point` (e.g. `"assets"`, or `""` to the root).
+    /// Sources with higher priority are checked first. Among sources with
+    /// equal priority the latest mounted is checked first
+    pub fn mount<P: AsRef<Path>, S: AssetSource>(point: P, source: S, priority: i32) {
+        Vfs::insert(point.as_ref(), Arc::new(source), priority);
+    }
+
+    /// Mount source like [`mount`](#method.mount), but only until the returned guard is dropped.
+    /// The VFS is shared by the whole process, so use it for temporary sources, e.g. in tests
+    pub fn mount_scoped<P: AsRef<Path>, S: AssetSource>(point: P, source: S, priority: i32) -> MountGuard {
+        MountGuard { id: Vfs::insert(point.as_ref(), Arc::new(source), priority) }
+    }
+
+    /// Unmount all sources from the `point`. Returns `false` if nothing was mounted
+    pub fn unmount<P: AsRef<Path>>(point: P) -> bool {
+        let point = normalize(point.as_ref());
+        let mut mounts = MOUNTS.write();
+        let count = mounts.len();
+
+        mounts.retain(|m| m.point != point);
+        mounts.len() != count
+    }
+
+    pub fn unmount_all() {
+        MOUNTS.write().clear();
+    }
+
+    /// Read whole file content
+    pub fn read<P: AsRef<Path>>(path: P) -> io::Result<Vec<u8>> {
+        match Vfs::resolve(path.as_ref()) {
+            Some((source, relative)) => source.read(&relative),
+            None => std::fs::read(path),
+        }
+    }
+
+    /// Read whole file content as UTF-8 string
+    pub fn read_to_string<P: AsRef<Path>>(path: P) -> io::Result<String> {
+        String::from_utf8(Vfs::read(path)?)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    pub fn exists<P: AsRef<Path>>(path: P) -> bool {
+        Vfs::resolve(path.as_ref()).is_some() || path.as_ref().is_file()
+    }
+
+    /// Check whether the file is provided by a mounted source rather than read from disk
+    pub fn is_mounted<P: AsRef<Path>>(path: P) -> bool {
+        Vfs::resolve(path.as_ref()).is_some()
+    }
+
+    /// Get modification time of the file, if its source provides it
+    pub fn modified<P: AsRef<Path>>(path: P) -> Option<SystemTime> {
+        match Vfs::resolve(path.as_ref()) {
+            Some((source, relative)) => source.modified(&relative),
+            None => std::fs::metadata(path).and_then(|m| m.modified()).ok(),
+        }
+    }
+
+    fn insert(point: &Path, source: Arc<dyn AssetSource>, priority: i32) -> u64 {
+        let id = NEXT_MOUNT_ID.fetch_add(1, Ordering::Relaxed);
+        let mut mounts = MOUNTS.write();
+        let index = mounts
+            .iter()
+            .position(|m| m.priority <= priority)
+            .unwrap_or(mounts.len());
+
+        mounts.insert(index, Mount {
+            id,
+            point: normalize(point),
+            priority,
+            source,
+        });
+
+        id
+    }
+
+    /// Find the source with the highest priority, which contains the file
+    fn resolve(path: &Path) -> Option<(Arc<dyn AssetSource>, PathBuf)> {
+        let path = normalize(path);
+
+        MOUNTS.read()
+            .iter()
+            .find_map(|mount| {
+                let relative = path.strip_prefix(&mount.point).ok()?;
+
+                if mount.source.exists(relative) {
+                    Some((mount.source.clone(), relative.to_owned()))
+                } else {
+                    None
+                }
+            })
+    }
+}
+
+/// Remove `.` components, so `./assets/a.png` and `assets/a.png` are the same file
+fn normalize(path: &Path) -> PathBuf {
+    path.components()
+        .filter(|c| !matches!(c, Component::CurDir))
+        .collect()
+}
+
+fn not_found(path: &Path) -> io::Error {
+    io::Error::new(io::ErrorKind::NotFound, format!("File '{}' not found", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use parking_lot::Mutex;
+
+    use super::*;
+
+    /// Tests, which mount sources, are serialized, since the VFS is shared by the whole process
+    static VFS_LOCK: Mutex<()> = parking_lot::const_mutex(());
+
+    #[test]
+    fn scoped_mount_is_unmounted_on_drop() {
+        let _lock = VFS_LOCK.lock();
+        let path = Path::new("scoped/file.txt");
+
+        {
+            let source = MemorySource::new().with_file("file.txt", b"data".to_vec());
+            let _guard = Vfs::mount_scoped("scoped", source, 0);
+
+            assert_eq!(Vfs::read(path).unwrap(), b"data");
+        }
+
+        assert!(!Vfs::is_mounted(path));
+    }
+
+    #[test]
+    fn directory_source_rejects_outer_paths() {
+        let source = DirectorySource::new("src");
+
+        assert!(source.read(Path::new("lib.rs")).is_ok());
+        assert!(source.read(Path::new("../Cargo.toml")).is_err());
+        assert!(!source.exists(Path::new("../Cargo.toml")));
+        assert!(source.read(&std::env::current_dir().unwrap().join("Cargo.toml")).is_err());
+    }
+}
@@ -0,0 +1,238 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+
+use as_any::AsAny;
+use serde::{Serialize, Deserialize, Serializer, Deserializer};
+
+use super::loading::{AssetId, LoadedAsset};
+
+/// Trait for custom asset types, which are stored in [`Assets`] collection
+/// and can be loaded with [`AssetLoader`](super::AssetLoader)s
+pub trait Asset: Send + Sync + 'static {
+    /// Character, which identifies asset type, like `AssetHandle<'T'>` for textures.
+    /// `'T'`, `'M'`, `'S'`, `'L'` and `'P'` (used by [`Prefab`](super::Prefab)) are reserved by the engine
+    const TYPE: char;
+}
+
+/// Typed handle of the custom [`Asset`]
+pub struct Handle<A: Asset> {
+    index: usize,
+    _marker: PhantomData<fn() -> A>,
+}
+
+impl<A: Asset> Handle<A> {
+    pub fn from_index(index: usize) -> Self {
+        Handle { index, _marker: PhantomData }
+    }
+
+    pub fn invalid() -> Self {
+        Handle::from_index(usize::MAX)
+    }
+
+    pub fn unwrap(&self) -> usize {
+        self.index
+    }
+
+    /// Get untyped [`AssetId`] of the handle
+    pub fn id(&self) -> AssetId {
+        AssetId::new(A::TYPE, self.index)
+    }
+}
+
+impl<A: Asset> Clone for Handle<A> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<A: Asset> Copy for Handle<A> {}
+
+impl<A: Asset> PartialEq for Handle<A> {
+    fn eq(&self, other: &Self) -> bool {
+        self.index == other.index
+    }
+}
+
+impl<A: Asset> Eq for Handle<A> {}
+
+impl<A: Asset> Hash for Handle<A> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.index.hash(state);
+    }
+}
+
+impl<A: Asset> fmt::Debug for Handle<A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Handle")
+         .field(&A::TYPE)
+         .field(&self.index)
+         .finish()
+    }
+}
+
+impl<A: Asset> Serialize for Handle<A> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.index.serialize(serializer)
+    }
+}
+
+impl<'de, A: Asset> Deserialize<'de> for Handle<A> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(Handle::from_index(usize::deserialize(deserializer)?))
+    }
+}
+
+impl<A: Asset> From<Handle<A>> for AssetId {
+    fn from(handle: Handle<A>) -> Self {
+        handle.id()
+    }
+}
+
+/// Generic storage of the custom assets. Assets, loaded from the same path, share one handle
+pub struct Assets<A: Asset> {
+    assets: Vec<Option<A>>,
+    paths: HashMap<PathBuf, Handle<A>>,
+}
+
+impl<A: Asset> Assets<A> {
+    pub fn new() -> Self {
+        Assets::default()
+    }
+
+    /// Add asset to the storage
+    pub fn add(&mut self, asset: A) -> Handle<A> {
+        let index = self.assets.len();
+        self.assets.push(Some(asset));
+        Handle::from_index(index)
+    }
+
+    /// Get asset. Returns `None` if the handle is invalid or the asset is not loaded yet
+    pub fn get(&self, handle: Handle<A>) -> Option<&A> {
+        self.assets.get(handle.index)?.as_ref()
+    }
+
+    pub fn get_mut(&mut self, handle: Handle<A>) -> Option<&mut A> {
+        self.assets.get_mut(handle.index)?.as_mut()
+    }
+
+    /// Get handle of the asset, which has been loaded from the `path`
+    pub fn handle_of<P: AsRef<Path>>(&self, path: P) -> Option<Handle<A>> {
+        self.paths.get(path.as_ref()).copied()
+    }
+
+    /// Get path, which the asset has been loaded from
+    pub fn path_of(&self, handle: Handle<A>) -> Option<&Path> {
+        self.paths
+            .iter()
+            .find(|(_, h)| **h == handle)
+            .map(|(path, _)| path.as_path())
+    }
+
+    /// Iterate over loaded assets
+    pub fn iter(&self) -> impl Iterator<Item = (Handle<A>, &A)> {
+        self.assets
+            .iter()
+            .enumerate()
+            .filter_map(|(i, a)| Some((Handle::from_index(i), a.as_ref()?)))
+    }
+
+    pub fn len(&self) -> usize {
+        self.assets.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.assets.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.assets.clear();
+        self.paths.clear();
+    }
+
+    /// Reserve handle for the asset, which is going to be loaded from the `path`
+    pub(crate) fn reserve<P: Into<PathBuf>>(&mut self, path: P) -> Handle<A> {
+        let index = self.assets.len();
+        let handle = Handle::from_index(index);
+
+        self.assets.push(None);
+        self.paths.insert(path.into(), handle);
+
+        handle
+    }
+}
+
+impl<A: Asset> Default for Assets<A> {
+    fn default() -> Self {
+        Assets {
+            assets: vec![],
+            paths: HashMap::new(),
+        }
+    }
+}
+
+/// Type-erased [`Assets`] storage, which is used inside the [`AssetManager`](super::AssetManager)
+pub(crate) trait AnyAssets: AsAny + Send + Sync {
+    fn set_loaded(&mut self, index: usize, data: LoadedAsset) -> Result<(), String>;
+
+    fn contains(&self, index: usize) -> bool;
+
+    fn paths(&self) -> Vec<(PathBuf, usize)>;
+
+    fn clear(&mut self);
+
+    /// Move assets of the `other` storage of the same type to the end of this one.
+    /// Returns index of the first moved asset
+    fn merge(&mut self, other: &mut dyn AnyAssets) -> Option<usize>;
+}
+
+impl<A: Asset> AnyAssets for Assets<A> {
+    fn set_loaded(&mut self, index: usize, data: LoadedAsset) -> Result<(), String> {
+        let asset = data.downcast::<A>().map_err(|_| {
+            format!("Invalid data of the asset '{}'", std::any::type_name::<A>())
+        })?;
+
+        match self.assets.get_mut(index) {
+            Some(slot) => {
+                *slot = Some(*asset);
+                Ok(())
+            },
+            None => Err(format!("Loaded asset {:?} doesn't exist", AssetId::new(A::TYPE, index))),
+        }
+    }
+
+    fn contains(&self, index: usize) -> bool {
+        index < self.assets.len()
+    }
+
+    fn paths(&self) -> Vec<(PathBuf, usize)> {
+        self.paths
+            .iter()
+            .map(|(path, handle)| (path.clone(), handle.index))
+            .collect()
+    }
+
+    fn clear(&mut self) {
+        Assets::clear(self);
+    }
+
+    fn merge(&mut self, other: &mut dyn AnyAssets) -> Option<usize> {
+        let other = other.as_any_mut().downcast_mut::<Assets<A>>()?;
+        let offset = self.assets.len();
+
+        for (path, handle) in other.paths.drain() {
+            self.paths.entry(path).or_insert(Handle::from_index(handle.index + offset));
+        }
+        self.assets.append(&mut other.assets);
+
+        Some(offset)
+    }
+}
+
+impl fmt::Debug for dyn AnyAssets {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Assets")
+    }
+}
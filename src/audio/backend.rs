@@ -0,0 +1,208 @@
+use std::time::Instant;
+
+use kira::{
+    clock::{ClockHandle, ClockSpeed},
+    dsp::Frame,
+    manager::{
+        AudioManager,
+        AudioManagerSettings,
+        Capacities,
+        backend::{
+            cpal::CpalBackend,
+            mock::{MockBackend, MockBackendSettings},
+        },
+        error::{AddClockError, AddSpatialSceneError, AddSubTrackError, PlaySoundError},
+    },
+    sound::SoundData,
+    spatial::scene::{SpatialSceneHandle, SpatialSceneSettings},
+    track::{TrackBuilder, TrackHandle},
+};
+use serde::{Serialize, Deserialize};
+
+use crate::audio::{error::AudioError, mixer::MAX_BUSES};
+
+/// Sample rate of the offline backend
+pub const MOCK_SAMPLE_RATE: u32 = 48_000;
+
+/// Sample rate of the real-time mock backend. Its output is discarded, so audio
+/// is processed coarsely, just to advance playback, tweens and clocks
+const MOCK_STEP_RATE: u32 = 1_000;
+
+/// Maximal time in seconds, which the mock backend processes at once. Longer
+/// pauses between updates (e.g. loading screens) are skipped
+const MAX_MOCK_STEP: f64 = 0.25;
+
+/// Output of the [`AudioManager`](super::AudioManager)
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum AudioBackend {
+    /// System audio device. If it's missing, the mock backend is used instead
+    #[default]
+    Cpal,
+    /// Silent backend, which processes sounds in real time without output, so
+    /// instances finish and events are sent as usual. Use it on CI or servers
+    Mock,
+    /// Silent backend, which processes sounds only with [`AudioManager::render`](super::AudioManager::render).
+    /// Use it to record audio or test mixing faster than real time
+    Offline,
+    /// Audio is disabled. Playback fails with [`AudioError::Disabled`]
+    None,
+}
+
+/// Kira audio manager with the backend, which is selected at runtime
+#[allow(clippy::large_enum_variant)]
+pub(crate) enum KiraAudioManager {
+    Cpal(AudioManager<CpalBackend>),
+    Mock {
+        manager: AudioManager<MockBackend>,
+        last_update: Instant,
+        backend: AudioBackend,
+        /// Part of the sample, which is left after the previous rendering
+        remainder: f64,
+    },
+}
+
+macro_rules! delegate {
+    ($manager:expr, $method:ident ( $($arg:expr),* )) => {
+        match $manager {
+            KiraAudioManager::Cpal(manager) => manager.$method($($arg),*),
+            KiraAudioManager::Mock { manager, .. } => manager.$method($($arg),*),
+        }
+    };
+}
+
+impl KiraAudioManager {
+    /// Create manager with the `backend`. Returns the backend, which is actually used
+    pub(crate) fn new(backend: AudioBackend, clock_capacity: usize) -> Result<(Self, AudioBackend), AudioError> {
+        if backend == AudioBackend::Cpal {
+            let settings = AudioManagerSettings {
+                capacities: capacities(clock_capacity),
+                ..Default::default()
+            };
+
+            match AudioManager::<CpalBackend>::new(settings) {
+                Ok(manager) => return Ok((KiraAudioManager::Cpal(manager), backend)),
+                Err(error) => log::warn!("Cannot use audio device ({error}), falling back to the mock backend"),
+            }
+        }
+
+        let sample_rate = match backend {
+            AudioBackend::Offline => MOCK_SAMPLE_RATE,
+            _ => MOCK_STEP_RATE,
+        };
+
+        let settings = AudioManagerSettings {
+            capacities: capacities(clock_capacity),
+            backend_settings: MockBackendSettings { sample_rate },
+            ..Default::default()
+        };
+
+        let mut manager = AudioManager::<MockBackend>::new(settings)
+            .map_err(|_| AudioError::BackendError(String::from("mock backend setup failed")))?;
+        manager.backend_mut().on_start_processing();
+
+        let backend = match backend {
+            AudioBackend::Cpal => AudioBackend::Mock,
+            backend => backend,
+        };
+
+        Ok((KiraAudioManager::Mock {
+            manager,
+            last_update: Instant::now(),
+            backend,
+            remainder: 0.0,
+        }, backend))
+    }
+
+    pub(crate) fn play<D: SoundData>(&mut self, data: D) -> Result<D::Handle, PlaySoundError<D::Error>> {
+        delegate!(self, play(data))
+    }
+
+    pub(crate) fn add_sub_track(&mut self, builder: TrackBuilder) -> Result<TrackHandle, AddSubTrackError> {
+        delegate!(self, add_sub_track(builder))
+    }
+
+    pub(crate) fn add_spatial_scene(&mut self, settings: SpatialSceneSettings) -> Result<SpatialSceneHandle, AddSpatialSceneError> {
+        delegate!(self, add_spatial_scene(settings))
+    }
+
+    pub(crate) fn add_clock(&mut self, speed: ClockSpeed) -> Result<ClockHandle, AddClockError> {
+        delegate!(self, add_clock(speed))
+    }
+
+    /// Free resources, which handles have been dropped. Device backends free them
+    /// on their own thread, so it only has effect with the silent ones
+    pub(crate) fn free_unused(&mut self) {
+        if let KiraAudioManager::Mock { manager, .. } = self {
+            manager.backend_mut().on_start_processing();
+        }
+    }
+
+    /// Process the time, elapsed since the previous update, with the mock backend.
+    /// Device backends process audio on their own thread. Offline and disabled
+    /// backends only receive commands
+    pub(crate) fn update(&mut self) {
+        if let KiraAudioManager::Mock { manager, last_update, backend: mock_backend, .. } = self {
+            let elapsed = last_update.elapsed().as_secs_f64().min(MAX_MOCK_STEP);
+            *last_update = Instant::now();
+
+            let backend = manager.backend_mut();
+            backend.on_start_processing();
+
+            if *mock_backend == AudioBackend::Mock {
+                for _ in 0..(elapsed * MOCK_STEP_RATE as f64) as usize {
+                    backend.process();
+                }
+            }
+        }
+    }
+
+    /// Process `seconds` of audio with the offline backend. Returns `None` with other backends
+    pub(crate) fn render(&mut self, seconds: f64) -> Option<Vec<Frame>> {
+        let KiraAudioManager::Mock { manager, backend: AudioBackend::Offline, remainder, .. } = self else {
+            return None;
+        };
+
+        let samples = seconds.max(0.0) * MOCK_SAMPLE_RATE as f64 + *remainder;
+        *remainder = samples.fract();
+
+        let backend = manager.backend_mut();
+        backend.on_start_processing();
+
+        Some((0..samples as usize).map(|_| backend.process()).collect())
+    }
+}
+
+/// Every bus has its own spatial scene. Twice as many scenes are reserved, since the
+/// scenes of the rebuilt buses are freed on the audio thread a bit later
+fn capacities(clock_capacity: usize) -> Capacities {
+    Capacities {
+        spatial_scene_capacity: MAX_BUSES * 2,
+        clock_capacity,
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_keeps_remainder() {
+        let (mut manager, backend) = KiraAudioManager::new(AudioBackend::Offline, 1).unwrap();
+        let sample = 1.0 / MOCK_SAMPLE_RATE as f64;
+
+        assert_eq!(backend, AudioBackend::Offline);
+        assert_eq!(manager.render(sample * 1.5).unwrap().len(), 1);
+        assert_eq!(manager.render(sample * 1.5).unwrap().len(), 2);
+        assert_eq!(manager.render(sample * 0.25).unwrap().len(), 0);
+        assert_eq!(manager.render(-1.0).unwrap().len(), 0);
+    }
+
+    #[test]
+    fn render_requires_offline_backend() {
+        let (mut manager, backend) = KiraAudioManager::new(AudioBackend::Mock, 1).unwrap();
+
+        assert_eq!(backend, AudioBackend::Mock);
+        assert!(manager.render(1.0).is_none());
+    }
+}
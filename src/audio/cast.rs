@@ -1,15 +1,128 @@
-use kira::spatial::emitter::EmitterHandle;
-use kira::tween::Tween;
+use kira::spatial::emitter::{EmitterHandle, EmitterSettings};
+use kira::tween::{Easing, Tween};
+use serde::{Serialize, Deserialize};
 
 use crate::error::FlatboxResult;
 use crate::audio::{
     error::AudioError,
+    mixer::Bus,
     AudioManager,
 };
 use crate::math::transform::Transform;
 
+/// Curve of the cast volume between the minimal and the maximal distance
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum Attenuation {
+    /// Volume doesn't depend on the distance
+    None,
+    #[default]
+    Linear,
+    InPowi(i32),
+    OutPowi(i32),
+    InOutPowi(i32),
+    InPowf(f64),
+    OutPowf(f64),
+    InOutPowf(f64),
+}
+
+impl From<Attenuation> for Option<Easing> {
+    fn from(attenuation: Attenuation) -> Self {
+        match attenuation {
+            Attenuation::None => None,
+            Attenuation::Linear => Some(Easing::Linear),
+            Attenuation::InPowi(power) => Some(Easing::InPowi(power)),
+            Attenuation::OutPowi(power) => Some(Easing::OutPowi(power)),
+            Attenuation::InOutPowi(power) => Some(Easing::InOutPowi(power)),
+            Attenuation::InPowf(power) => Some(Easing::InPowf(power)),
+            Attenuation::OutPowf(power) => Some(Easing::OutPowf(power)),
+            Attenuation::InOutPowf(power) => Some(Easing::InOutPowf(power)),
+        }
+    }
+}
+
+/// Spatial settings of the [`AudioCast`]
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CastSettings {
+    /// Distance, which the cast is heard at full volume within
+    pub min_distance: f32,
+    /// Distance, which the cast is inaudible beyond
+    pub max_distance: f32,
+    pub attenuation: Attenuation,
+    /// Pan sounds depending on the direction from the listener. Kira supports
+    /// spatialization only on or off, so there is no strength value
+    pub spatialization: bool,
+    /// Keep the emitter playing its sounds after the cast is despawned
+    pub persist: bool,
+}
+
+impl CastSettings {
+    pub fn new() -> Self {
+        CastSettings::default()
+    }
+
+    pub fn distances(mut self, min_distance: f32, max_distance: f32) -> Self {
+        self.min_distance = min_distance;
+        self.max_distance = max_distance;
+        self
+    }
+
+    pub fn attenuation(mut self, attenuation: Attenuation) -> Self {
+        self.attenuation = attenuation;
+        self
+    }
+
+    pub fn spatialization(mut self, spatialization: bool) -> Self {
+        self.spatialization = spatialization;
+        self
+    }
+
+    pub fn persist(mut self, persist: bool) -> Self {
+        self.persist = persist;
+        self
+    }
+
+    pub(crate) fn emitter_settings(&self) -> EmitterSettings {
+        EmitterSettings::new()
+            .distances((self.min_distance, self.max_distance))
+            .attenuation_function(self.attenuation)
+            .enable_spatialization(self.spatialization)
+            .persist_until_sounds_finish(self.persist)
+    }
+}
+
+impl Default for CastSettings {
+    fn default() -> Self {
+        CastSettings {
+            min_distance: 1.0,
+            max_distance: 100.0,
+            attenuation: Attenuation::Linear,
+            spatialization: true,
+            persist: false,
+        }
+    }
+}
+
+/// Sound emitter of the spatial scene. It's serialized as a description only, so
+/// deserialized and cloned casts get their own emitters with the next `processing_audio` run
+#[derive(Default, Serialize, Deserialize)]
 pub struct AudioCast {
-    pub(crate) handle: EmitterHandle,
+    /// Mixer bus of the cast sounds. The emitter is recreated, when it's changed
+    #[serde(default)]
+    pub bus: Bus,
+    /// Kira emitters can't be changed, so the emitter is recreated, when settings are changed.
+    /// Sounds, which are playing on the old one, are cut off unless it persists
+    #[serde(default)]
+    pub settings: CastSettings,
+    /// Emitter along with the identifier of its scene
+    #[serde(skip)]
+    pub(crate) handle: Option<(u64, EmitterHandle)>,
+    /// Settings of the created emitter
+    #[serde(skip)]
+    applied: CastSettings,
+    /// Smoothed occlusion, which is updated by the `audio_occlusion` system
+    #[serde(skip)]
+    pub(crate) occlusion: f32,
 }
 
 impl AudioCast {
@@ -19,17 +132,70 @@ impl AudioCast {
         audio_manager.new_cast()
     }
 
+    /// Check whether the emitter is created
+    pub fn is_created(&self) -> bool {
+        self.handle.is_some()
+    }
+
+    /// Route the cast to the mixer bus
+    pub fn with_bus(mut self, bus: Bus) -> Self {
+        self.bus = bus;
+        self
+    }
+
+    pub fn with_settings(mut self, settings: CastSettings) -> Self {
+        self.settings = settings;
+        self
+    }
+
+    /// Occlusion from the listeners in range `0.0..=1.0`
+    pub fn occlusion(&self) -> f32 {
+        self.occlusion
+    }
+
+    pub(crate) fn emitter(&self) -> Option<&EmitterHandle> {
+        self.handle.as_ref().map(|(_, handle)| handle)
+    }
+
+    /// Create emitter in the spatial scene of the cast bus, if it's missing, belongs to another scene or has outdated settings
+    pub(crate) fn restore(&mut self, audio_manager: &mut AudioManager, t: &Transform) -> FlatboxResult<()> {
+        let scene_id = audio_manager.scene_id(&self.bus);
+
+        if self.handle.as_ref().map(|(id, _)| *id) != Some(scene_id) || self.applied != self.settings {
+            self.handle = Some(audio_manager.add_emitter(&self.bus, t.translation, &self.settings)?);
+            self.applied = self.settings;
+        }
+
+        Ok(())
+    }
+
     pub(crate) fn set_transform(&mut self, t: &Transform) -> FlatboxResult<()> {
-        self.handle.set_position(t.translation, Tween::default()).map_err(|e| AudioError::from(e))?;
+        if let Some((_, handle)) = &mut self.handle {
+            handle.set_position(t.translation, Tween::default()).map_err(AudioError::from)?;
+        }
 
         Ok(())
     }
 }
 
+impl Clone for AudioCast {
+    fn clone(&self) -> Self {
+        AudioCast {
+            bus: self.bus.clone(),
+            settings: self.settings,
+            handle: None,
+            applied: CastSettings::default(),
+            occlusion: 0.0,
+        }
+    }
+}
+
 impl std::fmt::Debug for AudioCast {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.debug_tuple("AudioCast")
-         .field(&self.handle.id())
+        f.debug_struct("AudioCast")
+         .field("bus", &self.bus)
+         .field("settings", &self.settings)
+         .field("emitter", &self.emitter().map(|h| h.id()))
          .finish()
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,229 @@
+use std::sync::Arc;
+
+use kira::{
+    StartTime,
+    clock::{ClockHandle, ClockSpeed, ClockTime},
+};
+use parking_lot::Mutex;
+use serde::{Serialize, Deserialize};
+
+use crate::assets::AssetHandle;
+use crate::error::FlatboxResult;
+use crate::audio::{
+    AudioManager,
+    error::AudioError,
+    instance::{SoundInstance, PlayOptions, Tween},
+};
+
+/// Maximal number of the tempo clocks of the [`AudioManager`] by default
+pub const DEFAULT_CLOCK_CAPACITY: usize = 8;
+
+/// Unique identifier of the tempo clock
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ClockId(pub(crate) u64);
+
+/// Event, which is sent by `processing_audio` on every beat of the ticking clock.
+/// Can be read with `Events::get_handler::<Beat>()`
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Beat {
+    pub clock: ClockId,
+    /// Beat since the clock start, counting from zero
+    pub beat: u64,
+    pub bar: u64,
+    /// Beat within the bar, counting from zero
+    pub beat_in_bar: u32,
+}
+
+/// Musical tempo of the [`TempoClock`]
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Tempo {
+    /// Beats per minute
+    pub bpm: f64,
+    pub beats_per_bar: u32,
+}
+
+impl Tempo {
+    pub fn new(bpm: f64, beats_per_bar: u32) -> Self {
+        Tempo { bpm, beats_per_bar: beats_per_bar.max(1) }
+    }
+}
+
+impl Default for Tempo {
+    fn default() -> Self {
+        Tempo { bpm: 120.0, beats_per_bar: 4 }
+    }
+}
+
+/// Moment of the clock to start the sound at
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ClockStart {
+    NextBeat,
+    NextBar,
+    /// Beat since the clock start. Sound starts immediately, if the beat has passed
+    Beat(u64),
+}
+
+/// Clock, which ticks once per beat. It's created stopped with [`AudioManager::add_clock`].
+/// All the clones control the same clock, which is removed when the last one is dropped
+///
+/// # Usage example
+///
+/// ```rust
+/// let clock = asset_manager.audio.add_clock(Tempo::new(128.0, 4))?;
+/// clock.start()?;
+/// asset_manager.audio.play_at(kick, &clock, ClockStart::NextBar)?;
+/// ```
+#[derive(Clone)]
+pub struct TempoClock {
+    id: ClockId,
+    tempo: Arc<Mutex<Tempo>>,
+    handle: Arc<ClockHandle>,
+    /// Last beat, which has been reported with the [`Beat`] event
+    last_beat: Arc<Mutex<Option<u64>>>,
+}
+
+impl TempoClock {
+    pub fn id(&self) -> ClockId {
+        self.id
+    }
+
+    pub fn tempo(&self) -> Tempo {
+        *self.tempo.lock()
+    }
+
+    /// Change the tempo. Only the speed is tweened, bar length is changed at once
+    pub fn set_tempo(&self, tempo: Tempo, tween: Tween) -> FlatboxResult<()> {
+        let tempo = Tempo::new(tempo.bpm, tempo.beats_per_bar);
+        self.handle.set_speed(ClockSpeed::TicksPerMinute(tempo.bpm), tween).map_err(AudioError::from)?;
+        *self.tempo.lock() = tempo;
+
+        Ok(())
+    }
+
+    pub fn is_ticking(&self) -> bool {
+        self.handle.ticking()
+    }
+
+    /// Start or resume the clock
+    pub fn start(&self) -> FlatboxResult<()> {
+        self.handle.start().map_err(AudioError::from)?;
+        Ok(())
+    }
+
+    pub fn pause(&self) -> FlatboxResult<()> {
+        self.handle.pause().map_err(AudioError::from)?;
+        Ok(())
+    }
+
+    /// Stop the clock and reset it to the first beat
+    pub fn stop(&self) -> FlatboxResult<()> {
+        self.handle.stop().map_err(AudioError::from)?;
+        *self.last_beat.lock() = None;
+        Ok(())
+    }
+
+    /// Current beat since the clock start
+    pub fn beat(&self) -> u64 {
+        self.handle.time().ticks
+    }
+
+    pub fn bar(&self) -> u64 {
+        self.beat() / self.tempo().beats_per_bar as u64
+    }
+
+    /// Progress to the next beat in range `0.0..1.0`
+    pub fn beat_fraction(&self) -> f64 {
+        self.handle.fractional_position()
+    }
+
+    fn time(&self, start: ClockStart) -> ClockTime {
+        let now = self.handle.time();
+        let beats_per_bar = self.tempo().beats_per_bar as u64;
+
+        let ticks = match start {
+            ClockStart::NextBeat => now.ticks + 1,
+            ClockStart::NextBar => (now.ticks / beats_per_bar + 1) * beats_per_bar,
+            ClockStart::Beat(beat) => beat,
+        };
+
+        ClockTime { ticks, ..now }
+    }
+}
+
+impl std::fmt::Debug for TempoClock {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TempoClock")
+         .field("id", &self.id)
+         .field("tempo", &self.tempo())
+         .field("beat", &self.beat())
+         .finish()
+    }
+}
+
+impl AudioManager {
+    /// Create stopped clock with the `tempo`. Fails, when the [`clock_capacity`](#method.clock_capacity)
+    /// is reached. Removed clocks count until their last clone is dropped
+    pub fn add_clock(&self, tempo: Tempo) -> FlatboxResult<TempoClock> {
+        let tempo = Tempo::new(tempo.bpm, tempo.beats_per_bar);
+        let handle = self.inner().add_clock(ClockSpeed::TicksPerMinute(tempo.bpm)).map_err(AudioError::from)?;
+
+        let clock = TempoClock {
+            id: ClockId(self.next_clock.fetch_add(1, std::sync::atomic::Ordering::Relaxed)),
+            tempo: Arc::new(Mutex::new(tempo)),
+            handle: Arc::new(handle),
+            last_beat: Arc::new(Mutex::new(None)),
+        };
+
+        self.clocks.lock().push(clock.clone());
+
+        Ok(clock)
+    }
+
+    /// Stop sending [`Beat`] events of the clock. It's removed after its last clone is dropped
+    pub fn remove_clock(&self, clock: &TempoClock) {
+        self.clocks.lock().retain(|c| c.id != clock.id);
+    }
+
+    pub fn clocks(&self) -> Vec<TempoClock> {
+        self.clocks.lock().clone()
+    }
+
+    /// Play the sound, when the `clock` reaches the `start`
+    pub fn play_at(&self, handle: AssetHandle<'S'>, clock: &TempoClock, start: ClockStart) -> FlatboxResult<SoundInstance> {
+        self.play_with(handle, PlayOptions {
+            start_time: Some(StartTime::ClockTime(clock.time(start))),
+            ..Default::default()
+        })
+    }
+
+    /// Beats of the ticking clocks since the previous call
+    pub(crate) fn collect_beats(&self) -> Vec<Beat> {
+        let mut beats = vec![];
+
+        for clock in self.clocks.lock().iter() {
+            if !clock.is_ticking() {
+                continue;
+            }
+
+            let ticks = clock.beat();
+            let mut last = clock.last_beat.lock();
+
+            let beats_per_bar = clock.tempo().beats_per_bar as u64;
+            let from = last.map_or(0, |last| last + 1);
+
+            for beat in from..=ticks {
+                beats.push(Beat {
+                    clock: clock.id,
+                    beat,
+                    bar: beat / beats_per_bar,
+                    beat_in_bar: (beat % beats_per_bar) as u32,
+                });
+            }
+
+            *last = Some(ticks);
+        }
+
+        beats
+    }
+}
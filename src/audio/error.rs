@@ -4,7 +4,9 @@ use kira::{
         backend::cpal::Error as CpalError, 
         error::{AddSpatialSceneError, AddClockError, AddModulatorError, AddSubTrackError, PlaySoundError as KiraPlaySoundError}
     }, 
-    sound::FromFileError, CommandError
+    sound::FromFileError, 
+    spatial::scene::AddListenerError,
+    CommandError
 };
 
 #[non_exhaustive]
@@ -18,14 +20,33 @@ pub enum AudioError {
     AddModulatorError(#[from] AddModulatorError),
     #[error("Error creating a spatial scene")]
     AddSpatialSceneError(#[from] AddSpatialSceneError),
+    /// kira doesn't export its `AddEmitterError`, so only the message is kept
+    #[error("Error creating an audio cast: {0}")]
+    AddEmitterError(String),
+    #[error("Error creating an audio listener")]
+    AddListenerError(#[from] AddListenerError),
     #[error("Error creating a mixer sub-track")]
     AddSubTrackError(#[from] AddSubTrackError),
     #[error("Error loading or streaming an audio file")]
     FromFileError(#[from] FromFileError),
+    #[error("Invalid synth sound definition: {0}")]
+    SynthError(String),
+    #[error("Error reading an audio file")]
+    IoError(#[from] std::io::Error),
     #[error("Error sending a command to the audio thread")]
     CommandError(#[from] CommandError),
+    #[error("Cannot create audio backend: {0}")]
+    BackendError(String),
+    #[error("Audio is disabled")]
+    Disabled,
+    #[error("Mixer bus '{0}' doesn't exist")]
+    UnknownBus(String),
+    #[error("Cannot add mixer bus '{0}': the limit of {} buses is reached", crate::audio::MAX_BUSES)]
+    BusLimitReached(String),
     #[error("Audio playback error: {0}")]
     PlaySoundError(String),
+    #[error("Cannot append a recording with sample rate {1} to one with sample rate {0}")]
+    SampleRateMismatch(u32, u32),
 }
 
 impl<T> From<KiraPlaySoundError<T>> for AudioError {
@@ -0,0 +1,278 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use kira::{
+    CommandError,
+    StartTime,
+    Volume as KiraVolume,
+    tween::Value,
+    sound::{
+        FromFileError,
+        IntoOptionalRegion,
+        Region,
+        static_sound::StaticSoundHandle,
+        streaming::StreamingSoundHandle,
+    },
+    spatial::emitter::EmitterId,
+};
+use parking_lot::Mutex;
+
+pub use kira::{
+    sound::PlaybackState,
+    tween::Tween,
+};
+
+use crate::assets::{AssetHandle, SoundRegion};
+use crate::error::FlatboxResult;
+use crate::audio::{
+    error::AudioError,
+    mixer::Bus,
+    occlusion::OcclusionLevel,
+    volume::Volume,
+};
+
+/// Unique identifier of the played sound instance
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct InstanceId(pub(crate) u64);
+
+/// Event, which is sent by `processing_audio` when the sound instance is stopped
+/// or played to the end. Can be read with `Events::get_handler::<SoundFinished>()`
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct SoundFinished {
+    pub instance: InstanceId,
+    pub sound: AssetHandle<'S'>,
+}
+
+/// Handle of the played sound, which is returned by [`AudioManager::play`](super::AudioManager::play).
+/// All the clones of the handle control the same playback
+///
+/// # Usage example
+///
+/// ```rust
+/// let music = asset_manager.audio.play(handle)?;
+/// music.set_looped(true)?;
+/// // ...
+/// music.stop(Tween { duration: Duration::from_secs(2), ..Default::default() })?;
+/// ```
+#[derive(Clone)]
+pub struct SoundInstance {
+    id: InstanceId,
+    sound: AssetHandle<'S'>,
+    /// Emitter of the cast, which plays the sound
+    emitter: Option<EmitterId>,
+    handle: Arc<Mutex<InstanceHandle>>,
+    /// Occlusion of the cast sounds, which is applied on top of the volume
+    occlusion: Arc<OcclusionLevel>,
+}
+
+/// Handle of the decoded or streamed sound
+pub(crate) enum InstanceHandle {
+    Static(StaticSoundHandle),
+    Streaming(StreamingSoundHandle<FromFileError>),
+}
+
+macro_rules! delegate {
+    ($handle:expr, $method:ident ( $($arg:expr),* )) => {
+        match $handle {
+            InstanceHandle::Static(handle) => handle.$method($($arg),*),
+            InstanceHandle::Streaming(handle) => handle.$method($($arg),*),
+        }
+    };
+}
+
+impl InstanceHandle {
+    fn state(&self) -> PlaybackState { delegate!(self, state()) }
+    fn position(&self) -> f64 { delegate!(self, position()) }
+    fn pause(&mut self, tween: Tween) -> Result<(), CommandError> { delegate!(self, pause(tween)) }
+    fn resume(&mut self, tween: Tween) -> Result<(), CommandError> { delegate!(self, resume(tween)) }
+    fn stop(&mut self, tween: Tween) -> Result<(), CommandError> { delegate!(self, stop(tween)) }
+    fn seek_to(&mut self, position: f64) -> Result<(), CommandError> { delegate!(self, seek_to(position)) }
+    fn seek_by(&mut self, amount: f64) -> Result<(), CommandError> { delegate!(self, seek_by(amount)) }
+    fn set_playback_rate(&mut self, rate: f64, tween: Tween) -> Result<(), CommandError> { delegate!(self, set_playback_rate(rate, tween)) }
+    fn set_panning(&mut self, panning: f64, tween: Tween) -> Result<(), CommandError> { delegate!(self, set_panning(panning, tween)) }
+    fn set_volume(&mut self, volume: f64, tween: Tween) -> Result<(), CommandError> { delegate!(self, set_volume(volume, tween)) }
+
+    fn set_loop_region(&mut self, region: impl IntoOptionalRegion) -> Result<(), CommandError> {
+        delegate!(self, set_loop_region(region))
+    }
+}
+
+impl SoundInstance {
+    pub(crate) fn new(
+        id: InstanceId,
+        sound: AssetHandle<'S'>,
+        emitter: Option<EmitterId>,
+        occlusion: Arc<OcclusionLevel>,
+        handle: InstanceHandle,
+    ) -> Self {
+        SoundInstance {
+            id,
+            sound,
+            emitter,
+            handle: Arc::new(Mutex::new(handle)),
+            occlusion,
+        }
+    }
+
+    pub(crate) fn emitter(&self) -> Option<EmitterId> {
+        self.emitter
+    }
+
+    /// Check whether the sound is streamed from the file
+    pub fn is_streaming(&self) -> bool {
+        matches!(*self.handle.lock(), InstanceHandle::Streaming(_))
+    }
+
+    pub fn id(&self) -> InstanceId {
+        self.id
+    }
+
+    /// Handle of the played sound asset
+    pub fn sound(&self) -> AssetHandle<'S'> {
+        self.sound
+    }
+
+    pub fn state(&self) -> PlaybackState {
+        self.handle.lock().state()
+    }
+
+    /// Check whether the sound is playing now, including fading out before pause or stop
+    pub fn is_playing(&self) -> bool {
+        matches!(self.state(), PlaybackState::Playing | PlaybackState::Pausing | PlaybackState::Stopping)
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.state() == PlaybackState::Paused
+    }
+
+    /// Check whether the sound is stopped or played to the end. Finished instance can't be resumed
+    pub fn is_finished(&self) -> bool {
+        self.state() == PlaybackState::Stopped
+    }
+
+    /// Current playback position in seconds
+    pub fn position(&self) -> f64 {
+        self.handle.lock().position()
+    }
+
+    /// Fade out with the `tween` and pause playback
+    pub fn pause(&self, tween: Tween) -> FlatboxResult<()> {
+        self.handle.lock().pause(tween).map_err(AudioError::from)?;
+        Ok(())
+    }
+
+    /// Resume paused playback, fading in with the `tween`
+    pub fn resume(&self, tween: Tween) -> FlatboxResult<()> {
+        self.handle.lock().resume(tween).map_err(AudioError::from)?;
+        Ok(())
+    }
+
+    /// Fade out with the `tween` and stop playback
+    pub fn stop(&self, tween: Tween) -> FlatboxResult<()> {
+        self.handle.lock().stop(tween).map_err(AudioError::from)?;
+        Ok(())
+    }
+
+    /// Move playback to the `position` in seconds
+    pub fn seek_to(&self, position: f64) -> FlatboxResult<()> {
+        self.handle.lock().seek_to(position).map_err(AudioError::from)?;
+        Ok(())
+    }
+
+    /// Move playback by `amount` of seconds. Negative amount seeks backwards
+    pub fn seek_by(&self, amount: f64) -> FlatboxResult<()> {
+        self.handle.lock().seek_by(amount).map_err(AudioError::from)?;
+        Ok(())
+    }
+
+    /// Set playback rate, where `1.0` is the normal speed. It changes pitch too
+    pub fn set_playback_rate(&self, rate: f64, tween: Tween) -> FlatboxResult<()> {
+        self.handle.lock().set_playback_rate(rate, tween).map_err(AudioError::from)?;
+        Ok(())
+    }
+
+    /// Set panning from `0.0` (left) to `1.0` (right). Default is `0.5`
+    pub fn set_panning(&self, panning: f64, tween: Tween) -> FlatboxResult<()> {
+        self.handle.lock().set_panning(panning.clamp(0.0, 1.0), tween).map_err(AudioError::from)?;
+        Ok(())
+    }
+
+    pub fn set_volume(&self, volume: Volume, tween: Tween) -> FlatboxResult<()> {
+        self.handle.lock().set_volume(volume.amplitude(), tween).map_err(AudioError::from)?;
+        Ok(())
+    }
+
+    /// Set occlusion gain, which is applied on top of the volume, and low-pass cutoff.
+    /// They are shared with the audio thread, so no commands are sent
+    pub(crate) fn set_occlusion(&self, gain: f64, cutoff: f64) {
+        self.occlusion.set(gain, cutoff);
+    }
+
+    /// Loop the whole sound or play it once
+    pub fn set_looped(&self, looped: bool) -> FlatboxResult<()> {
+        let mut handle = self.handle.lock();
+
+        if looped {
+            handle.set_loop_region(..)
+        } else {
+            handle.set_loop_region(None)
+        }.map_err(AudioError::from)?;
+
+        Ok(())
+    }
+}
+
+impl std::fmt::Debug for SoundInstance {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SoundInstance")
+         .field("id", &self.id)
+         .field("sound", &self.sound)
+         .field("state", &self.state())
+         .finish()
+    }
+}
+
+/// Overrides of the sound settings for the single playback
+#[derive(Clone, Debug, Default)]
+pub(crate) struct PlayOptions {
+    pub bus: Option<Bus>,
+    pub volume: Option<Volume>,
+    pub loop_region: Option<SoundRegion>,
+    pub fade_in: Option<Tween>,
+    pub start_time: Option<StartTime>,
+}
+
+impl PlayOptions {
+    /// Apply overrides to the fields of the static or streaming sound settings
+    pub(crate) fn apply(
+        &self,
+        volume: &mut Value<KiraVolume>,
+        loop_region: &mut Option<Region>,
+        fade_in: &mut Option<Tween>,
+        start_time: &mut StartTime,
+    ) {
+        if let Some(value) = self.volume {
+            *volume = value.amplitude().into();
+        }
+
+        if let Some(region) = self.loop_region {
+            *loop_region = Some(region.into());
+        }
+
+        if self.fade_in.is_some() {
+            *fade_in = self.fade_in;
+        }
+
+        if let Some(time) = self.start_time {
+            *start_time = time;
+        }
+    }
+}
+
+/// Linear tween of the `duration`. Use it to fade sound instances
+pub fn fade(duration: Duration) -> Tween {
+    Tween {
+        duration,
+        ..Default::default()
+    }
+}
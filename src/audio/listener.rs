@@ -1,16 +1,61 @@
 use kira::{spatial::listener::ListenerHandle, tween::Tween};
+use nalgebra::UnitQuaternion;
+use serde::{Serialize, Deserialize};
 
 use crate::{
     audio::{
         AudioManager,
         error::AudioError,
+        mixer::Bus,
     },
     math::Transform, 
     error::FlatboxResult,
 };
 
+/// Settings of the [`AudioListener`]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ListenerSettings {
+    /// Buses, which the listener hears. Empty list means all buses
+    pub buses: Vec<Bus>,
+    /// Sync orientation with the entity rotation. Otherwise the listener keeps facing the default direction
+    pub follow_rotation: bool,
+}
+
+impl ListenerSettings {
+    pub fn new() -> Self {
+        ListenerSettings::default()
+    }
+
+    pub fn bus(mut self, bus: Bus) -> Self {
+        self.buses.push(bus);
+        self
+    }
+
+    pub fn follow_rotation(mut self, follow_rotation: bool) -> Self {
+        self.follow_rotation = follow_rotation;
+        self
+    }
+}
+
+impl Default for ListenerSettings {
+    fn default() -> Self {
+        ListenerSettings {
+            buses: vec![],
+            follow_rotation: true,
+        }
+    }
+}
+
+/// Listener of the spatial scenes. Like [`AudioCast`](crate::audio::AudioCast), it's serialized
+/// as a description and the kira listeners are recreated with the next `processing_audio` run.
+/// Every mixer bus has own scene, so the listener consists of one kira listener per bus
+#[derive(Default, Serialize, Deserialize)]
 pub struct AudioListener {
-    pub(crate) handle: ListenerHandle,
+    #[serde(default)]
+    pub settings: ListenerSettings,
+    #[serde(skip)]
+    pub(crate) handles: Vec<(u64, ListenerHandle)>,
 }
 
 impl AudioListener {
@@ -20,18 +65,67 @@ impl AudioListener {
         audio_manager.new_listener()
     }
 
+    pub fn with_settings(mut self, settings: ListenerSettings) -> Self {
+        self.settings = settings;
+        self
+    }
+
+    /// Check whether the listener is created
+    pub fn is_created(&self) -> bool {
+        !self.handles.is_empty()
+    }
+
+    /// Create listeners in the scenes of the heard buses, which are missing, and drop ones of the replaced or unheard scenes
+    pub(crate) fn restore(&mut self, audio_manager: &mut AudioManager, t: &Transform) -> FlatboxResult<()> {
+        let scene_ids = audio_manager.scene_ids(&self.settings.buses);
+        self.handles.retain(|(id, _)| scene_ids.contains(id));
+
+        if self.handles.len() < scene_ids.len() {
+            let missing = scene_ids.into_iter()
+                .filter(|id| !self.handles.iter().any(|(existing, _)| existing == id))
+                .collect::<Vec<_>>();
+
+            let handles = audio_manager.add_listeners(&missing, t.translation, *self.orientation(t).quaternion())?;
+            self.handles.extend(handles);
+        }
+
+        Ok(())
+    }
+
     pub(crate) fn set_transform(&mut self, t: &Transform) -> FlatboxResult<()> {
-        self.handle.set_position(t.translation, Tween::default()).map_err(|e| AudioError::from(e))?;
-        self.handle.set_orientation(t.rotation, Tween::default()).map_err(|e| AudioError::from(e))?;
+        let orientation = self.orientation(t);
+
+        for (_, handle) in &mut self.handles {
+            handle.set_position(t.translation, Tween::default()).map_err(AudioError::from)?;
+            handle.set_orientation(orientation, Tween::default()).map_err(AudioError::from)?;
+        }
 
         Ok(())
     }
+
+    fn orientation(&self, t: &Transform) -> UnitQuaternion<f32> {
+        if self.settings.follow_rotation {
+            t.rotation
+        } else {
+            UnitQuaternion::identity()
+        }
+    }
+}
+
+impl Clone for AudioListener {
+    fn clone(&self) -> Self {
+        AudioListener {
+            settings: self.settings.clone(),
+            handles: vec![],
+        }
+    }
 }
 
 impl std::fmt::Debug for AudioListener {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.debug_tuple("AudioListener")
-         .field(&self.handle.id())
+        f.debug_struct("AudioListener")
+         .field("settings", &self.settings)
+         .field("listeners", &self.handles.iter().map(|(_, h)| h.id()).collect::<Vec<_>>())
          .finish()
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,196 @@
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use kira::{
+    spatial::scene::{SpatialSceneHandle, SpatialSceneSettings},
+    track::{
+        TrackBuilder,
+        TrackHandle,
+        TrackId,
+        TrackRoutes,
+        effect::{
+            compressor::CompressorBuilder,
+            delay::DelayBuilder,
+            filter::{FilterBuilder, FilterMode},
+            reverb::ReverbBuilder,
+        },
+    },
+};
+use serde::{Serialize, Deserialize, Serializer, ser::SerializeSeq};
+
+use crate::audio::{
+    error::AudioError,
+    volume::Volume,
+    backend::KiraAudioManager,
+};
+
+/// Maximal number of the mixer buses, including the [`Bus::STANDARD`] ones
+pub const MAX_BUSES: usize = 16;
+
+/// Identifiers of the bus spatial scenes. They are unique across audio managers,
+/// so emitters and listeners of the replaced scenes can be detected
+static NEXT_SCENE_ID: AtomicU64 = AtomicU64::new(0);
+
+/// Mixer bus, which [`Sound`](super::Sound)s and [`AudioCast`](super::AudioCast)s are routed to.
+/// All buses are routed to the [`Bus::Master`] one
+#[derive(Clone, Debug, Default, Hash, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Bus {
+    #[default]
+    Master,
+    Music,
+    Sfx,
+    Voice,
+    Ui,
+    /// Custom bus, added with [`AudioManager::add_bus`](super::AudioManager::add_bus)
+    Named(String),
+}
+
+impl Bus {
+    /// Buses, which are created with every [`AudioManager`](super::AudioManager)
+    pub const STANDARD: [Bus; 5] = [Bus::Master, Bus::Music, Bus::Sfx, Bus::Voice, Bus::Ui];
+}
+
+impl fmt::Display for Bus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Bus::Master => write!(f, "Master"),
+            Bus::Music => write!(f, "Music"),
+            Bus::Sfx => write!(f, "SFX"),
+            Bus::Voice => write!(f, "Voice"),
+            Bus::Ui => write!(f, "UI"),
+            Bus::Named(name) => write!(f, "{name}"),
+        }
+    }
+}
+
+/// Effect, which is applied to all the sounds of the bus
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum BusEffect {
+    /// Remove frequencies above the `cutoff` (in Hz)
+    LowPass { cutoff: f64, resonance: f64 },
+    /// Remove frequencies below the `cutoff` (in Hz)
+    HighPass { cutoff: f64, resonance: f64 },
+    /// Reverberation. `mix` is a dry/wet ratio in range `0.0..=1.0`
+    Reverb { feedback: f64, damping: f64, mix: f64 },
+    /// Echo with the `time` in seconds and the `feedback` amplitude
+    Delay { time: f64, feedback: f64, mix: f64 },
+    /// Dynamic range compression. `threshold` is in decibels
+    Compressor { threshold: f64, ratio: f64, attack: Duration, release: Duration, mix: f64 },
+}
+
+impl BusEffect {
+    fn add_to(&self, builder: TrackBuilder) -> TrackBuilder {
+        match *self {
+            BusEffect::LowPass { cutoff, resonance } => builder.with_effect(
+                FilterBuilder::new().mode(FilterMode::LowPass).cutoff(cutoff).resonance(resonance)
+            ),
+            BusEffect::HighPass { cutoff, resonance } => builder.with_effect(
+                FilterBuilder::new().mode(FilterMode::HighPass).cutoff(cutoff).resonance(resonance)
+            ),
+            BusEffect::Reverb { feedback, damping, mix } => builder.with_effect(
+                ReverbBuilder::new().feedback(feedback).damping(damping).mix(mix)
+            ),
+            BusEffect::Delay { time, feedback, mix } => builder.with_effect(
+                DelayBuilder::new().delay_time(time).feedback(feedback).mix(mix)
+            ),
+            BusEffect::Compressor { threshold, ratio, attack, release, mix } => builder.with_effect(
+                CompressorBuilder::new()
+                    .threshold(threshold)
+                    .ratio(ratio)
+                    .attack_duration(attack)
+                    .release_duration(release)
+                    .mix(mix)
+            ),
+        }
+    }
+}
+
+/// Volume and effects of the bus
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BusSettings {
+    pub volume: Volume,
+    pub muted: bool,
+    pub effects: Vec<BusEffect>,
+}
+
+impl BusSettings {
+    pub fn new() -> Self {
+        BusSettings::default()
+    }
+
+    pub fn volume(mut self, volume: Volume) -> Self {
+        self.volume = volume;
+        self
+    }
+
+    pub fn muted(mut self, muted: bool) -> Self {
+        self.muted = muted;
+        self
+    }
+
+    pub fn effect(mut self, effect: BusEffect) -> Self {
+        self.effects.push(effect);
+        self
+    }
+
+    /// Linear amplitude of the bus output
+    pub fn amplitude(&self) -> f64 {
+        if self.muted { 0.0 } else { self.volume.amplitude() }
+    }
+}
+
+/// Mixer sub-track of the bus with its own spatial scene, since spatial
+/// sounds are routed to the tracks through the scene listeners
+pub(crate) struct MixerBus {
+    pub(crate) bus: Bus,
+    pub(crate) settings: BusSettings,
+    pub(crate) scene_id: u64,
+    pub(crate) scene: SpatialSceneHandle,
+    pub(crate) track: TrackHandle,
+}
+
+impl MixerBus {
+    pub(crate) fn build(
+        manager: &mut KiraAudioManager,
+        bus: Bus,
+        settings: BusSettings,
+        parent: Option<TrackId>,
+        cast_count: usize,
+        listener_count: usize,
+    ) -> Result<Self, AudioError> {
+        let mut builder = TrackBuilder::new().volume(settings.amplitude());
+        if let Some(parent) = parent {
+            builder = builder.routes(TrackRoutes::parent(parent));
+        }
+
+        for effect in &settings.effects {
+            builder = effect.add_to(builder);
+        }
+
+        let track = manager.add_sub_track(builder)?;
+        let scene = manager.add_spatial_scene(
+            SpatialSceneSettings::new()
+                .emitter_capacity(cast_count)
+                .listener_capacity(listener_count)
+        )?;
+
+        Ok(MixerBus {
+            bus,
+            settings,
+            scene_id: NEXT_SCENE_ID.fetch_add(1, Ordering::Relaxed),
+            scene,
+            track,
+        })
+    }
+}
+
+/// Serialize buses as their settings
+pub(crate) fn serialize_buses<S: Serializer>(buses: &[MixerBus], serializer: S) -> Result<S::Ok, S::Error> {
+    let mut seq = serializer.serialize_seq(Some(buses.len()))?;
+    for bus in buses {
+        seq.serialize_element(&(&bus.bus, &bus.settings))?;
+    }
+    seq.end()
+}
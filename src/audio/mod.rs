@@ -1,14 +1,12 @@
 use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::fmt;
 use kira::{ 
-    spatial::{scene::{
-        SpatialSceneHandle, 
-        SpatialSceneSettings
-    }, emitter::EmitterSettings, listener::ListenerSettings}, 
-    manager::{
-        AudioManagerSettings,
-        backend::cpal::CpalBackend,
-    },
+    spatial::{
+        emitter::{EmitterHandle, EmitterId}, 
+        listener::{ListenerSettings as KiraListenerSettings, ListenerHandle},
+    }, 
+    OutputDestination,
 };
 use nalgebra::{Vector3, Quaternion};
 use parking_lot::{Mutex, MutexGuard};
@@ -25,104 +23,504 @@ use serde::{
 #[allow(unused_imports)]
 use crate::assets::{
     AssetHandle,
+    AssetMapping,
+    AssetSource,
+    MapAssets,
+    Vfs,
     asset_manager::AssetManager,
 };
 use crate::error::FlatboxResult;
+use crate::math::transform::Transform;
 
+pub mod backend;
 pub mod cast;
+pub mod clock;
 pub mod error;
+pub mod instance;
 pub mod listener;
+pub mod mixer;
+pub mod music;
+pub mod occlusion;
+pub mod recording;
 pub mod sound;
+pub mod synth;
 pub mod volume;
 
+pub use backend::*;
 pub use cast::*;
+pub use clock::*;
 pub use error::*;
+pub use instance::*;
 pub use listener::*;
+pub use mixer::*;
+pub use music::*;
+pub use occlusion::*;
+pub use recording::*;
 pub use sound::*;
+pub use synth::*;
 pub use volume::*;
 
-type KiraAudioManager = kira::manager::AudioManager<CpalBackend>; 
-
 /// Main audio managment struct. It's actually a part of [`AssetManager`]
 #[derive(Serialize)]
 pub struct AudioManager {
     pub sounds: Vec<Sound>,
     cast_count: usize,
     listener_count: usize,
-
+    #[serde(serialize_with = "serialize_buses")]
+    buses: Vec<MixerBus>,
+    pub playlists: Vec<Playlist>,
+    /// Backend, which has been requested. It's opened again by [`with_requested_backend`](#method.with_requested_backend)
+    #[serde(rename = "backend")]
+    requested_backend: AudioBackend,
+    clock_capacity: usize,
+
+    /// Settings of the `audio_occlusion` system
+    #[serde(skip_serializing)]
+    pub occlusion: OcclusionSettings,
+    #[serde(skip_serializing)]
+    backend: AudioBackend,
     #[serde(skip_serializing)]
     manager: Arc<Mutex<KiraAudioManager>>,
     #[serde(skip_serializing)]
-    scene: SpatialSceneHandle,
+    instances: Mutex<Vec<SoundInstance>>,
+    #[serde(skip_serializing)]
+    next_instance: AtomicU64,
+    #[serde(skip_serializing)]
+    music: MusicPlayer,
+    #[serde(skip_serializing)]
+    clocks: Mutex<Vec<TempoClock>>,
+    #[serde(skip_serializing)]
+    next_clock: AtomicU64,
 }
 
 impl AudioManager {
+    /// Create audio manager with the system audio device
     pub fn new(
         cast_count: usize,
         listener_count: usize,
     ) -> FlatboxResult<Self> {
-        let settings = SpatialSceneSettings::new()
-            .emitter_capacity(cast_count)
-            .listener_capacity(listener_count);
-        
-        let mut manager = KiraAudioManager::new(AudioManagerSettings::default()).map_err(|e| AudioError::from(e))?;
-        let scene = manager.add_spatial_scene(settings).map_err(|e| AudioError::from(e))?;
+        AudioManager::with_backend(cast_count, listener_count, AudioBackend::default())
+    }
+
+    pub fn with_backend(
+        cast_count: usize,
+        listener_count: usize,
+        backend: AudioBackend,
+    ) -> FlatboxResult<Self> {
+        AudioManager::with_clock_capacity(cast_count, listener_count, backend, DEFAULT_CLOCK_CAPACITY)
+    }
+
+    /// Create audio manager, which can hold up to `clock_capacity` [`TempoClock`]s
+    pub fn with_clock_capacity(
+        cast_count: usize,
+        listener_count: usize,
+        backend: AudioBackend,
+        clock_capacity: usize,
+    ) -> FlatboxResult<Self> {
+        let requested_backend = backend;
+        let (mut manager, backend) = KiraAudioManager::new(backend, clock_capacity)?;
+
+        let mut buses: Vec<MixerBus> = vec![];
+        for bus in Bus::STANDARD {
+            let parent = buses.first().map(|master| master.track.id());
+            buses.push(MixerBus::build(&mut manager, bus, BusSettings::default(), parent, cast_count, listener_count)?);
+        }
         
         Ok(AudioManager { 
             sounds: vec![],
             cast_count,
             listener_count,
+            buses,
+            playlists: vec![],
+            requested_backend,
+            clock_capacity,
+            occlusion: OcclusionSettings::default(),
+            backend,
             manager: Arc::new(Mutex::new(manager)),
-            scene,
+            instances: Mutex::new(vec![]),
+            next_instance: AtomicU64::new(0),
+            music: MusicPlayer::default(),
+            clocks: Mutex::new(vec![]),
+            next_clock: AtomicU64::new(0),
         })
     }
 
+    /// Copy sounds, playlists and buses into the new manager. The copy doesn't open the
+    /// audio device, since it's usually only saved, e.g. in the [`Scene`](crate::assets::Scene),
+    /// or merged into another manager. The requested backend is kept, so it's used again,
+    /// when the copy is saved and loaded
+    pub fn snapshot(&self) -> FlatboxResult<AudioManager> {
+        self.copy_with_backend(AudioBackend::None)
+    }
+
+    /// Copy sounds, playlists and buses into the new manager, which opens the requested backend.
+    /// Deserialized managers don't open the audio device, so it's used to restore the live one,
+    /// e.g. by [`SaveLoad::load`](crate::assets::SaveLoad::load)
+    pub fn with_requested_backend(&self) -> FlatboxResult<AudioManager> {
+        self.copy_with_backend(self.requested_backend)
+    }
+
+    fn copy_with_backend(&self, backend: AudioBackend) -> FlatboxResult<AudioManager> {
+        let mut audio = AudioManager::with_clock_capacity(self.cast_count, self.listener_count, backend, self.clock_capacity)?;
+        audio.requested_backend = self.requested_backend;
+        audio.sounds = self.sounds.clone();
+        audio.playlists = self.playlists.clone();
+
+        for (bus, settings) in self.buses() {
+            audio.add_bus(bus.clone(), settings.clone())?;
+        }
+
+        Ok(audio)
+    }
+
     pub fn new_cast(
         &mut self, 
     ) -> AudioCast {
-        let handle = self.scene.add_emitter(
-            Vector3::identity(),
-            EmitterSettings::default(),
-        ).expect("Cannot create audio cast");
-
-        AudioCast { handle }
+        let mut cast = AudioCast::default();
+        cast.restore(self, &Transform::default()).expect("Cannot create audio cast");
+        cast
     }
 
     pub fn new_listener(
         &mut self,
     ) -> AudioListener {
-        let handle = self.scene.add_listener(
-            Vector3::identity(),
-            Quaternion::identity(),
-            ListenerSettings::default(),
-        ).expect("Cannot create audio cast");
+        let mut listener = AudioListener::default();
+        listener.restore(self, &Transform::default()).expect("Cannot create audio listener");
+        listener
+    }
+
+    /// Add emitter to the spatial scene of the bus. Returns identifier of the scene
+    /// along with the emitter. Used to restore deserialized [`AudioCast`]s
+    pub(crate) fn add_emitter(&mut self, bus: &Bus, position: Vector3<f32>, settings: &CastSettings) -> FlatboxResult<(u64, EmitterHandle)> {
+        let bus = self.mixer_bus_mut(bus);
+        let handle = bus.scene.add_emitter(
+            position,
+            settings.emitter_settings(),
+        ).map_err(|e| AudioError::AddEmitterError(e.to_string()))?;
+
+        Ok((bus.scene_id, handle))
+    }
 
-        AudioListener { handle }
+    /// Add listeners to the spatial scenes with `scene_ids`.
+    /// Used to restore deserialized [`AudioListener`]s
+    pub(crate) fn add_listeners(
+        &mut self, 
+        scene_ids: &[u64],
+        position: Vector3<f32>, 
+        orientation: Quaternion<f32>,
+    ) -> FlatboxResult<Vec<(u64, ListenerHandle)>> {
+        let mut handles = vec![];
+
+        for bus in self.buses.iter_mut().filter(|bus| scene_ids.contains(&bus.scene_id)) {
+            let handle = bus.scene.add_listener(
+                position,
+                orientation,
+                KiraListenerSettings::new().track(&bus.track),
+            ).map_err(AudioError::from)?;
+
+            handles.push((bus.scene_id, handle));
+        }
+
+        Ok(handles)
     }
 
-    pub fn play(&self, handle: AssetHandle<'S'>) -> FlatboxResult<()>{
-        match self.get_sound(handle) {
-            Some(sound) => {
-                self.inner()
-                    .play(sound.static_data.clone())
-                    .map_err(|e| AudioError::from(e))?;
+    /// Identifier of the bus spatial scene, which emitters are created in
+    pub(crate) fn scene_id(&self, bus: &Bus) -> u64 {
+        self.mixer_bus(bus).scene_id
+    }
+
+    /// Identifiers of the spatial scenes of the `buses`. Empty list means all buses
+    pub(crate) fn scene_ids(&self, buses: &[Bus]) -> Vec<u64> {
+        self.buses.iter()
+            .filter(|bus| buses.is_empty() || buses.contains(&bus.bus))
+            .map(|bus| bus.scene_id)
+            .collect()
+    }
+
+    /// Add mixer bus or replace settings of the existing one. Sounds, which are
+    /// playing on the replaced bus, are cut off. Replacing [`Bus::Master`] rebuilds all buses.
+    /// Up to [`MAX_BUSES`] buses can be added
+    pub fn add_bus(&mut self, bus: Bus, settings: BusSettings) -> FlatboxResult<()> {
+        match self.buses.iter().position(|b| b.bus == bus) {
+            Some(index) if self.buses[index].settings == settings => Ok(()),
+            Some(index) => {
+                self.buses[index].settings = settings;
+                self.rebuild_bus(index)
             },
+            None if self.buses.len() >= MAX_BUSES => Err(AudioError::BusLimitReached(bus.to_string()).into()),
             None => {
-                log::error!("Sound with handle {handle:?} not found!");
+                let master = self.buses[0].track.id();
+                let bus = MixerBus::build(&mut self.inner(), bus, settings, Some(master), self.cast_count, self.listener_count)?;
+                self.buses.push(bus);
+                Ok(())
             },
         }
+    }
+
+    /// Buses and their settings. [`Bus::Master`] is the first one
+    pub fn buses(&self) -> impl Iterator<Item = (&Bus, &BusSettings)> {
+        self.buses.iter().map(|bus| (&bus.bus, &bus.settings))
+    }
+
+    pub fn bus_settings(&self, bus: &Bus) -> Option<&BusSettings> {
+        self.buses.iter().find(|b| b.bus == *bus).map(|b| &b.settings)
+    }
+
+    pub fn set_bus_volume(&mut self, bus: &Bus, volume: Volume, tween: Tween) -> FlatboxResult<()> {
+        let bus = self.existing_bus_mut(bus)?;
+        bus.settings.volume = volume;
+        bus.track.set_volume(bus.settings.amplitude(), tween).map_err(AudioError::from)?;
+
+        Ok(())
+    }
+
+    pub fn set_bus_muted(&mut self, bus: &Bus, muted: bool) -> FlatboxResult<()> {
+        let bus = self.existing_bus_mut(bus)?;
+        bus.settings.muted = muted;
+        bus.track.set_volume(bus.settings.amplitude(), Tween::default()).map_err(AudioError::from)?;
+
+        Ok(())
+    }
+
+    /// Replace effects of the bus. See [`add_bus`](#method.add_bus)
+    pub fn set_bus_effects(&mut self, bus: &Bus, effects: Vec<BusEffect>) -> FlatboxResult<()> {
+        let mut settings = self.existing_bus_mut(bus)?.settings.clone();
+        settings.effects = effects;
+
+        self.add_bus(bus.clone(), settings)
+    }
+
+    /// Rebuild the bus or all of them for [`Bus::Master`]. Old buses are dropped
+    /// first, so their spatial scenes don't exceed the capacity
+    fn rebuild_bus(&mut self, index: usize) -> FlatboxResult<()> {
+        let range = if index == 0 { 0..self.buses.len() } else { index..index + 1 };
+        let rebuilt = self.buses.drain(range.clone())
+            .map(|bus| (bus.bus, bus.settings))
+            .collect::<Vec<_>>();
+
+        self.inner().free_unused();
+
+        for (i, (bus, settings)) in range.zip(rebuilt) {
+            let parent = (i != 0).then(|| self.buses[0].track.id());
+            let bus = MixerBus::build(&mut self.inner(), bus, settings, parent, self.cast_count, self.listener_count)?;
+            self.buses.insert(i, bus);
+        }
+
+        Ok(())
+    }
+
+    /// Bus, which is used for routing. Unknown buses fall back to the master one
+    fn mixer_bus(&self, bus: &Bus) -> &MixerBus {
+        self.buses.iter()
+            .find(|b| b.bus == *bus)
+            .unwrap_or(&self.buses[0])
+    }
+
+    fn mixer_bus_mut(&mut self, bus: &Bus) -> &mut MixerBus {
+        let index = self.buses.iter().position(|b| b.bus == *bus).unwrap_or(0);
+        &mut self.buses[index]
+    }
+
+    fn existing_bus_mut(&mut self, bus: &Bus) -> FlatboxResult<&mut MixerBus> {
+        Ok(self.buses.iter_mut()
+            .find(|b| b.bus == *bus)
+            .ok_or(AudioError::UnknownBus(bus.to_string()))?)
+    }
+
+    /// Play loaded sound. Returned [`SoundInstance`] controls the playback
+    pub fn play(&self, handle: AssetHandle<'S'>) -> FlatboxResult<SoundInstance> {
+        self.play_with(handle, PlayOptions::default())
+    }
+
+    /// Play sound, overriding its settings with the `options`
+    pub(crate) fn play_with(&self, handle: AssetHandle<'S'>, options: PlayOptions) -> FlatboxResult<SoundInstance> {
+        if self.backend == AudioBackend::None {
+            return Err(AudioError::Disabled.into());
+        }
+
+        let sound = match self.get_sound(handle) {
+            Some(sound) if sound.is_loaded() => sound,
+            Some(_) => return Err(AudioError::PlaySoundError(format!("Sound with handle {handle:?} is not loaded yet")).into()),
+            None => return Err(AudioError::PlaySoundError(format!("Sound with handle {handle:?} not found")).into()),
+        };
+
+        // Sounds of the casts are routed through the bus scenes
+        let emitter = sound.cast_id.filter(|_| options.bus.is_none());
+        let destination: OutputDestination = match (&options.bus, emitter) {
+            (Some(bus), _) => (&self.mixer_bus(bus).track).into(),
+            (None, Some(cast_id)) => cast_id.into(),
+            (None, None) => (&self.mixer_bus(&sound.bus).track).into(),
+        };
+
+        // Sounds of the casts are filtered by the occlusion
+        let occlusion = OcclusionLevel::bypass();
+
+        let sound_handle = if sound.streaming {
+            let mut data = sound.open_stream()?;
+            options.apply(&mut data.settings.volume, &mut data.settings.loop_region, &mut data.settings.fade_in_tween, &mut data.settings.start_time);
+            data.settings.output_destination = destination;
+
+            let sound_handle = match emitter {
+                Some(_) => self.inner().play(LowPassData::new(data, occlusion.clone())),
+                None => self.inner().play(data),
+            }.map_err(AudioError::from)?;
+
+            InstanceHandle::Streaming(sound_handle)
+        } else {
+            let mut data = sound.static_data.clone().unwrap();
+            options.apply(&mut data.settings.volume, &mut data.settings.loop_region, &mut data.settings.fade_in_tween, &mut data.settings.start_time);
+            data.settings.output_destination = destination;
+
+            let sound_handle = match emitter {
+                Some(_) => self.inner().play(LowPassData::new(data, occlusion.clone())),
+                None => self.inner().play(data),
+            }.map_err(AudioError::from)?;
+
+            InstanceHandle::Static(sound_handle)
+        };
+
+        let id = InstanceId(self.next_instance.fetch_add(1, Ordering::Relaxed));
+        let instance = SoundInstance::new(id, handle, emitter, occlusion, sound_handle);
+        self.instances.lock().push(instance.clone());
+
+        Ok(instance)
+    }
+
+    /// Backend, which is actually used. It differs from the requested one, if the audio device is missing
+    pub fn backend(&self) -> AudioBackend {
+        self.backend
+    }
+
+    /// Backend, which has been requested on creation. It's saved along with the manager
+    pub fn requested_backend(&self) -> AudioBackend {
+        self.requested_backend
+    }
+
+    /// Maximal number of the [`TempoClock`]s
+    pub fn clock_capacity(&self) -> usize {
+        self.clock_capacity
+    }
+
+    /// Process audio with the mock backend. It's called by `processing_audio`
+    pub(crate) fn update_backend(&self) {
+        self.inner().update();
+    }
+
+    /// Instances, which haven't finished yet
+    pub fn instances(&self) -> Vec<SoundInstance> {
+        self.instances.lock().clone()
+    }
+
+    /// Unfinished instances of the sound
+    pub fn instances_of(&self, handle: AssetHandle<'S'>) -> Vec<SoundInstance> {
+        self.instances.lock()
+            .iter()
+            .filter(|instance| instance.sound() == handle)
+            .cloned()
+            .collect()
+    }
+
+    /// Stop all the played sounds
+    pub fn stop_all(&self, tween: Tween) -> FlatboxResult<()> {
+        for instance in self.instances.lock().iter() {
+            instance.stop(tween)?;
+        }
 
         Ok(())
     }
 
-    pub fn create_sound(
+    /// Attenuate and filter instances of the cast emitter with the `occlusion`
+    pub(crate) fn apply_occlusion(&self, emitter: EmitterId, occlusion: f32) {
+        let (volume, cutoff) = self.occlusion.apply(occlusion);
+
+        for instance in self.instances.lock().iter().filter(|instance| instance.emitter() == Some(emitter)) {
+            instance.set_occlusion(volume, cutoff);
+        }
+    }
+
+    /// Forget finished instances. Returns events about them
+    pub(crate) fn collect_finished(&self) -> Vec<SoundFinished> {
+        let mut finished = vec![];
+
+        self.instances.lock().retain(|instance| {
+            if instance.is_finished() {
+                finished.push(SoundFinished {
+                    instance: instance.id(),
+                    sound: instance.sound(),
+                });
+                return false;
+            }
+
+            true
+        });
+
+        finished
+    }
+
+    pub fn create_sound<P: AsRef<std::path::Path>>(
+        &mut self,
+        path: P,
+    ) -> FlatboxResult<AssetHandle<'S'>> {
+        let sound = Sound::new_from_file(path)?;
+        Ok(self.push_sound(sound))
+    }
+
+    /// Add sound from the encoded data, e.g. generated or downloaded one
+    pub fn create_sound_from_bytes(
         &mut self,
-        path: &'static str,
+        bytes: impl Into<Arc<[u8]>>,
     ) -> FlatboxResult<AssetHandle<'S'>> {
+        let sound = Sound::new_from_bytes(bytes)?;
+        Ok(self.push_sound(sound))
+    }
+
+    /// Add sound from the file of the asset source, which is not mounted to the [`Vfs`]
+    pub fn create_sound_from_asset_source<P: AsRef<std::path::Path>>(
+        &mut self,
+        source: &dyn AssetSource,
+        path: P,
+    ) -> FlatboxResult<AssetHandle<'S'>> {
+        let sound = Sound::new_from_asset_source(source, path)?;
+        Ok(self.push_sound(sound))
+    }
+
+    /// Add procedurally generated sound
+    pub fn create_synth_sound(
+        &mut self,
+        synth: SynthSound,
+    ) -> AssetHandle<'S'> {
+        self.push_sound(Sound::new_synth(synth))
+    }
+
+    fn push_sound(&mut self, sound: Sound) -> AssetHandle<'S'> {
         let index = self.sounds.len();
-        self.sounds.push(Sound::new_from_file(path)?);
+        self.sounds.push(sound);
 
-        Ok(AssetHandle::from_index(index))
+        AssetHandle::from_index(index)
+    }
+
+    /// Add sound, which is decoded while playing instead of loading. Use it for long music tracks
+    pub fn create_streaming_sound<P: Into<std::path::PathBuf>>(
+        &mut self,
+        path: P,
+    ) -> FlatboxResult<AssetHandle<'S'>> {
+        let path = path.into();
+        if !Vfs::exists(&path) {
+            return Err(AudioError::PlaySoundError(format!("Streamed file '{}' doesn't exist", path.display())).into());
+        }
+
+        Ok(self.push_sound(Sound::new_streaming(path)))
+    }
+
+    /// Push sound, which data is going to be loaded in the background
+    pub(crate) fn push_empty_sound(
+        &mut self,
+        path: impl Into<std::path::PathBuf>,
+    ) -> AssetHandle<'S'> {
+        let index = self.sounds.len();
+        self.sounds.push(Sound::new_empty(path));
+
+        AssetHandle::from_index(index)
     }
 
     pub fn clone_sound(
@@ -148,7 +546,10 @@ impl AudioManager {
     }
 
     pub fn cleanup(&mut self){
+        self.music.reset();
+        self.clocks.lock().clear();
         self.sounds.clear();
+        self.playlists.clear();
     }
 
     fn inner(&self) -> MutexGuard<KiraAudioManager> {
@@ -181,6 +582,10 @@ impl<'de> Deserialize<'de> for AudioManager {
             Sounds,
             CastCount,
             ListenerCount,
+            Buses,
+            Playlists,
+            Backend,
+            ClockCapacity,
         }
 
         struct AudioManagerVisitor;
@@ -199,9 +604,20 @@ impl<'de> Deserialize<'de> for AudioManager {
                 let sounds: Vec<Sound> = seq.next_element()?.ok_or_else(|| DeError::invalid_length(0, &self))?;
                 let cast_count: usize = seq.next_element()?.ok_or_else(|| DeError::invalid_length(1, &self))?;
                 let listener_count: usize = seq.next_element()?.ok_or_else(|| DeError::invalid_length(2, &self))?;
-
-                let mut audio_manager = AudioManager::new(cast_count, listener_count).expect("Cannot create audio manager");
+                let buses: Vec<(Bus, BusSettings)> = seq.next_element()?.unwrap_or_default();
+                let playlists: Vec<Playlist> = seq.next_element()?.unwrap_or_default();
+                let backend: AudioBackend = seq.next_element()?.unwrap_or_default();
+                let clock_capacity: usize = seq.next_element()?.unwrap_or(DEFAULT_CLOCK_CAPACITY);
+
+                // Audio device isn't opened, since deserialized managers are usually scene assets
+                let mut audio_manager = AudioManager::with_clock_capacity(cast_count, listener_count, AudioBackend::None, clock_capacity)
+                    .map_err(DeError::custom)?;
+                audio_manager.requested_backend = backend;
                 audio_manager.sounds.extend(sounds);
+                audio_manager.playlists.extend(playlists);
+                for (bus, settings) in buses {
+                    audio_manager.add_bus(bus, settings).map_err(DeError::custom)?;
+                }
 
                 Ok(audio_manager)
             }
@@ -213,6 +629,10 @@ impl<'de> Deserialize<'de> for AudioManager {
                 let mut sounds: Option<Vec<Sound>> = None;
                 let mut cast_count: Option<usize> = None;
                 let mut listener_count: Option<usize> = None;
+                let mut buses: Option<Vec<(Bus, BusSettings)>> = None;
+                let mut playlists: Option<Vec<Playlist>> = None;
+                let mut backend: Option<AudioBackend> = None;
+                let mut clock_capacity: Option<usize> = None;
                 
                 while let Some(key) = map.next_key()? {
                     match key {
@@ -234,15 +654,50 @@ impl<'de> Deserialize<'de> for AudioManager {
                             }
                             listener_count = Some(map.next_value()?);
                         },
+                        AudioManagerField::Buses => {
+                            if buses.is_some() {
+                                return Err(DeError::duplicate_field("buses"));
+                            }
+                            buses = Some(map.next_value()?);
+                        },
+                        AudioManagerField::Playlists => {
+                            if playlists.is_some() {
+                                return Err(DeError::duplicate_field("playlists"));
+                            }
+                            playlists = Some(map.next_value()?);
+                        },
+                        AudioManagerField::Backend => {
+                            if backend.is_some() {
+                                return Err(DeError::duplicate_field("backend"));
+                            }
+                            backend = Some(map.next_value()?);
+                        },
+                        AudioManagerField::ClockCapacity => {
+                            if clock_capacity.is_some() {
+                                return Err(DeError::duplicate_field("clock_capacity"));
+                            }
+                            clock_capacity = Some(map.next_value()?);
+                        },
                     }
                 }
 
                 let sounds = sounds.ok_or_else(|| DeError::missing_field("sounds"))?;
                 let cast_count = cast_count.ok_or_else(|| DeError::missing_field("cast_count"))?;
                 let listener_count = listener_count.ok_or_else(|| DeError::missing_field("listener_count"))?;
-
-                let mut audio_manager = AudioManager::new(cast_count, listener_count).expect("Cannot create audio manager");
+                let buses = buses.unwrap_or_default();
+                let playlists = playlists.unwrap_or_default();
+                let backend = backend.unwrap_or_default();
+                let clock_capacity = clock_capacity.unwrap_or(DEFAULT_CLOCK_CAPACITY);
+
+                // Audio device isn't opened, since deserialized managers are usually scene assets
+                let mut audio_manager = AudioManager::with_clock_capacity(cast_count, listener_count, AudioBackend::None, clock_capacity)
+                    .map_err(DeError::custom)?;
+                audio_manager.requested_backend = backend;
                 audio_manager.sounds.extend(sounds);
+                audio_manager.playlists.extend(playlists);
+                for (bus, settings) in buses {
+                    audio_manager.add_bus(bus, settings).map_err(DeError::custom)?;
+                }
 
                 Ok(audio_manager)
             }
@@ -252,13 +707,17 @@ impl<'de> Deserialize<'de> for AudioManager {
             "sounds", 
             "cast_count", 
             "listener_count",
+            "buses",
+            "playlists",
+            "backend",
+            "clock_capacity",
         ];
 
         deserializer.deserialize_struct("AudioManager", FIELDS, AudioManagerVisitor)
     }
 }
 
-#[derive(Debug, Default, Clone, Hash, PartialEq, Eq)]
+#[derive(Debug, Default, Clone, Hash, PartialEq, Eq, Serialize, Deserialize)]
 pub struct AudioStorage {
     pub sounds: Vec<AssetHandle<'S'>>,
 }
@@ -269,6 +728,12 @@ impl AudioStorage {
     }
 }
 
+impl MapAssets for AudioStorage {
+    fn map_assets(&mut self, mapping: &AssetMapping) {
+        self.sounds.map_assets(mapping);
+    }
+}
+
 /// Creates [`AudioStorage`] like a `vec![]` of handles
 /// 
 /// # Usage example
@@ -0,0 +1,338 @@
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Serialize, Deserialize};
+
+use crate::assets::{AssetHandle, SoundRegion};
+use crate::error::FlatboxResult;
+use crate::audio::{
+    AudioManager,
+    Sound,
+    instance::{SoundInstance, PlayOptions, PlaybackState, Tween, fade},
+    mixer::Bus,
+    volume::Volume,
+};
+
+/// Music track of the [`Playlist`]. Tracks are streamed from the files
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MusicTrack {
+    pub path: PathBuf,
+    /// Looped section of the track. The part before its start is the intro,
+    /// which is played once. Looped track is played until [`AudioManager::next_track`]
+    pub loop_region: Option<SoundRegion>,
+    pub volume: Volume,
+    /// Length of the track in seconds. Streamed tracks don't report their length, so
+    /// it's required for the crossfade: if it's set, the next track starts crossfading
+    /// before the end of this one, otherwise it starts after the end without crossfade
+    pub duration: Option<f64>,
+}
+
+impl MusicTrack {
+    pub fn new<P: Into<PathBuf>>(path: P) -> Self {
+        MusicTrack {
+            path: path.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Play the part before `start` once and loop the rest until `end`
+    pub fn looped(mut self, start: f64, end: Option<f64>) -> Self {
+        self.loop_region = Some(SoundRegion { start, end });
+        self
+    }
+
+    pub fn volume(mut self, volume: Volume) -> Self {
+        self.volume = volume;
+        self
+    }
+
+    pub fn duration(mut self, duration: f64) -> Self {
+        self.duration = Some(duration);
+        self
+    }
+}
+
+impl Default for MusicTrack {
+    fn default() -> Self {
+        MusicTrack {
+            path: PathBuf::new(),
+            loop_region: None,
+            volume: Volume::default(),
+            duration: None,
+        }
+    }
+}
+
+/// List of the music tracks, which are played by the [`MusicPlayer`]
+///
+/// # Usage example
+///
+/// ```rust
+/// let playlist = asset_manager.audio.add_playlist(Playlist {
+///     tracks: vec![
+///         MusicTrack::new("assets/music/theme.ogg").looped(12.5, None),
+///         MusicTrack::new("assets/music/forest.ogg").duration(184.0),
+///     ],
+///     repeat: true,
+///     crossfade: Duration::from_secs(3),
+///     ..Default::default()
+/// });
+///
+/// asset_manager.audio.play_playlist(playlist)?;
+/// // ...
+/// asset_manager.audio.next_track()?;
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Playlist {
+    pub tracks: Vec<MusicTrack>,
+    /// Start again after the last track
+    pub repeat: bool,
+    /// Play tracks in random order
+    pub shuffle: bool,
+    /// Duration of the crossfade between tracks
+    pub crossfade: Duration,
+}
+
+/// State of the music playback. It's a part of the [`AudioManager`] and is
+/// updated by `processing_audio`. Played playlist is copied, so its changes
+/// take effect after the next [`AudioManager::play_playlist`]
+#[derive(Debug)]
+pub struct MusicPlayer {
+    /// Mixer bus of the music. Default is [`Bus::Music`]
+    pub bus: Bus,
+    playlist: Option<AssetHandle<'L'>>,
+    /// Copy of the played playlist
+    tracks: Playlist,
+    order: Vec<usize>,
+    position: usize,
+    current: Option<SoundInstance>,
+    /// Streamed sounds of the playlist tracks
+    sounds: Vec<AssetHandle<'S'>>,
+}
+
+impl MusicPlayer {
+    /// Played playlist
+    pub fn playlist(&self) -> Option<AssetHandle<'L'>> {
+        self.playlist
+    }
+
+    /// Index of the played track in the playlist
+    pub fn track_index(&self) -> Option<usize> {
+        self.current.as_ref()?;
+        self.order.get(self.position).copied()
+    }
+
+    /// Instance of the played track
+    pub fn current(&self) -> Option<&SoundInstance> {
+        self.current.as_ref()
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.current.as_ref().is_some_and(|c| c.is_playing())
+    }
+
+    /// Stop the music and forget the playlist. The bus is kept
+    pub(crate) fn reset(&mut self) {
+        if let Some(current) = self.current.take() {
+            let _ = current.stop(Tween::default());
+        }
+
+        *self = MusicPlayer {
+            bus: self.bus.clone(),
+            ..Default::default()
+        };
+    }
+}
+
+impl Default for MusicPlayer {
+    fn default() -> Self {
+        MusicPlayer {
+            bus: Bus::Music,
+            playlist: None,
+            tracks: Playlist::default(),
+            order: vec![],
+            position: 0,
+            current: None,
+            sounds: vec![],
+        }
+    }
+}
+
+impl AudioManager {
+    pub fn add_playlist(&mut self, playlist: Playlist) -> AssetHandle<'L'> {
+        let index = self.playlists.len();
+        self.playlists.push(playlist);
+
+        AssetHandle::from_index(index)
+    }
+
+    pub fn get_playlist(&self, handle: AssetHandle<'L'>) -> Option<&Playlist> {
+        self.playlists.get(handle.unwrap())
+    }
+
+    /// Changes of the played playlist take effect after the next [`play_playlist`](#method.play_playlist)
+    pub fn get_playlist_mut(&mut self, handle: AssetHandle<'L'>) -> Option<&mut Playlist> {
+        self.playlists.get_mut(handle.unwrap())
+    }
+
+    pub fn music(&self) -> &MusicPlayer {
+        &self.music
+    }
+
+    pub fn music_mut(&mut self) -> &mut MusicPlayer {
+        &mut self.music
+    }
+
+    /// Start the playlist from the beginning, crossfading from the current track
+    pub fn play_playlist(&mut self, handle: AssetHandle<'L'>) -> FlatboxResult<()> {
+        let playlist = self.get_playlist(handle)
+            .ok_or(crate::Result::yell(format!("Playlist with handle {handle:?} not found")))?
+            .clone();
+
+        let sounds = playlist.tracks.iter()
+            .map(|track| self.streaming_sound(&track.path))
+            .collect::<FlatboxResult<Vec<_>>>()?;
+
+        self.music.playlist = Some(handle);
+        self.music.order = track_order(&playlist);
+        self.music.position = 0;
+        self.music.sounds = sounds;
+        self.music.tracks = playlist.clone();
+
+        self.start_track(&playlist)
+    }
+
+    /// Crossfade to the next track of the playlist. Music stops after the last track, unless the playlist is repeated
+    pub fn next_track(&mut self) -> FlatboxResult<()> {
+        if self.music.playlist.is_none() {
+            return Ok(());
+        }
+
+        let playlist = self.music.tracks.clone();
+
+        self.music.position += 1;
+
+        if self.music.position >= self.music.order.len() {
+            if !playlist.repeat {
+                return self.stop_music(fade(playlist.crossfade));
+            }
+
+            self.music.order = track_order(&playlist);
+            self.music.position = 0;
+        }
+
+        self.start_track(&playlist)
+    }
+
+    pub fn stop_music(&mut self, tween: Tween) -> FlatboxResult<()> {
+        self.music.playlist = None;
+
+        if let Some(current) = self.music.current.take() {
+            current.stop(tween)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn pause_music(&mut self, tween: Tween) -> FlatboxResult<()> {
+        match &self.music.current {
+            Some(current) => current.pause(tween),
+            None => Ok(()),
+        }
+    }
+
+    pub fn resume_music(&mut self, tween: Tween) -> FlatboxResult<()> {
+        match &self.music.current {
+            Some(current) => current.resume(tween),
+            None => Ok(()),
+        }
+    }
+
+    /// Advance the playlist, when the current track is finished or is about to finish
+    pub(crate) fn update_music(&mut self) -> FlatboxResult<()> {
+        let (Some(current), Some(_)) = (&self.music.current, self.music.playlist) else {
+            return Ok(());
+        };
+
+        let playlist = &self.music.tracks;
+
+        if current.is_finished() {
+            return self.next_track();
+        }
+
+        let track = self.music.order.get(self.music.position).and_then(|&i| playlist.tracks.get(i));
+        let fade_start = match track {
+            Some(MusicTrack { loop_region: None, duration: Some(duration), .. }) => duration - playlist.crossfade.as_secs_f64(),
+            _ => return Ok(()),
+        };
+
+        if !playlist.crossfade.is_zero() && current.state() == PlaybackState::Playing && current.position() >= fade_start {
+            return self.next_track();
+        }
+
+        Ok(())
+    }
+
+    /// Fade out the current track and fade in the one at the current position
+    fn start_track(&mut self, playlist: &Playlist) -> FlatboxResult<()> {
+        let crossfade = (!playlist.crossfade.is_zero()).then(|| fade(playlist.crossfade));
+
+        if let Some(current) = self.music.current.take() {
+            current.stop(crossfade.unwrap_or_default())?;
+        }
+
+        let Some(&index) = self.music.order.get(self.music.position) else {
+            return Ok(());
+        };
+
+        let (Some(track), Some(&sound)) = (playlist.tracks.get(index), self.music.sounds.get(index)) else {
+            return Err(crate::Result::yell(format!("Playlist has no track {index}")));
+        };
+
+        let instance = self.play_with(sound, PlayOptions {
+            bus: Some(self.music.bus.clone()),
+            volume: Some(track.volume),
+            loop_region: track.loop_region,
+            fade_in: crossfade,
+            ..Default::default()
+        })?;
+
+        self.music.current = Some(instance);
+
+        Ok(())
+    }
+
+    /// Find the streamed sound of the file or add new one
+    fn streaming_sound(&mut self, path: &Path) -> FlatboxResult<AssetHandle<'S'>> {
+        let existing = self.sounds.iter()
+            .position(|sound: &Sound| sound.is_streaming() && sound.path() == Some(path));
+
+        match existing {
+            Some(index) => Ok(AssetHandle::from_index(index)),
+            None => self.create_streaming_sound(path),
+        }
+    }
+}
+
+/// Indices of the playlist tracks in order of playback
+fn track_order(playlist: &Playlist) -> Vec<usize> {
+    let mut order = (0..playlist.tracks.len()).collect::<Vec<_>>();
+
+    if playlist.shuffle {
+        let mut seed = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(1, |time| time.as_nanos() as u64) | 1;
+
+        // Fisher-Yates shuffle with xorshift generator
+        for i in (1..order.len()).rev() {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            order.swap(i, (seed % (i as u64 + 1)) as usize);
+        }
+    }
+
+    order
+}
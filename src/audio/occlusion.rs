@@ -0,0 +1,179 @@
+use std::f64::consts::TAU;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use kira::{
+    OutputDestination,
+    clock::clock_info::ClockInfoProvider,
+    dsp::Frame,
+    modulator::value_provider::ModulatorValueProvider,
+    sound::{Sound as KiraSound, SoundData},
+};
+use serde::{Serialize, Deserialize};
+
+/// Cutoff, which the low-pass filter of the occluded sound is bypassed above
+const BYPASS_CUTOFF: f64 = 20_000.0;
+
+/// Time in seconds, which the occlusion gain takes to reach the new value. It
+/// prevents clicks, when the gain is changed between audio frames
+const GAIN_RAMP: f64 = 0.02;
+
+/// Component of the entity with the `BodyHandle`, which sets how much its collider
+/// occludes sounds. Colliders without it use [`OcclusionSettings::collider_factor`]
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AudioOccluder {
+    /// Occlusion in range `0.0..=1.0`, where `1.0` blocks the sound completely
+    pub factor: f32,
+}
+
+impl AudioOccluder {
+    pub fn new(factor: f32) -> Self {
+        AudioOccluder { factor }
+    }
+}
+
+impl Default for AudioOccluder {
+    fn default() -> Self {
+        AudioOccluder { factor: 1.0 }
+    }
+}
+
+/// Settings of the `audio_occlusion` system. Factors of the colliders between the
+/// listener and the cast are summed up to the occlusion in range `0.0..=1.0`
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OcclusionSettings {
+    /// Occlusion factor of the colliders without [`AudioOccluder`]
+    pub collider_factor: f32,
+    /// Volume amplitude of the fully occluded sounds
+    pub volume: f64,
+    /// Low-pass cutoff in Hz of the fully occluded sounds
+    pub cutoff: f64,
+    /// Time of the transition to the new occlusion
+    pub smoothing: Duration,
+}
+
+impl OcclusionSettings {
+    /// Volume amplitude and low-pass cutoff of the `occlusion`
+    pub fn apply(&self, occlusion: f32) -> (f64, f64) {
+        let occlusion = occlusion.clamp(0.0, 1.0) as f64;
+        let volume = 1.0 + (self.volume - 1.0) * occlusion;
+        // Interpolate cutoff exponentially, since pitch is perceived logarithmically
+        let cutoff = BYPASS_CUTOFF * (self.cutoff.max(1.0) / BYPASS_CUTOFF).powf(occlusion);
+
+        (volume, cutoff)
+    }
+}
+
+impl Default for OcclusionSettings {
+    fn default() -> Self {
+        OcclusionSettings {
+            collider_factor: 0.5,
+            volume: 0.3,
+            cutoff: 800.0,
+            smoothing: Duration::from_millis(200),
+        }
+    }
+}
+
+/// Occlusion gain and low-pass cutoff of the cast sound. They are shared with the
+/// audio thread, so occlusion is updated every frame without sending commands
+pub(crate) struct OcclusionLevel {
+    gain: AtomicU64,
+    cutoff: AtomicU64,
+}
+
+impl OcclusionLevel {
+    /// Level of the sound, which is not occluded
+    pub(crate) fn bypass() -> Arc<Self> {
+        Arc::new(OcclusionLevel {
+            gain: AtomicU64::new(1f64.to_bits()),
+            cutoff: AtomicU64::new(BYPASS_CUTOFF.to_bits()),
+        })
+    }
+
+    pub(crate) fn set(&self, gain: f64, cutoff: f64) {
+        self.gain.store(gain.to_bits(), Ordering::Relaxed);
+        self.cutoff.store(cutoff.to_bits(), Ordering::Relaxed);
+    }
+
+    fn gain(&self) -> f64 {
+        f64::from_bits(self.gain.load(Ordering::Relaxed))
+    }
+
+    fn cutoff(&self) -> f64 {
+        f64::from_bits(self.cutoff.load(Ordering::Relaxed))
+    }
+}
+
+/// Sound data, which is played through the low-pass filter and the gain of the shared level
+pub(crate) struct LowPassData<D> {
+    data: D,
+    level: Arc<OcclusionLevel>,
+}
+
+impl<D> LowPassData<D> {
+    pub(crate) fn new(data: D, level: Arc<OcclusionLevel>) -> Self {
+        LowPassData { data, level }
+    }
+}
+
+impl<D: SoundData> SoundData for LowPassData<D> {
+    type Error = D::Error;
+    type Handle = D::Handle;
+
+    fn into_sound(self) -> Result<(Box<dyn KiraSound>, Self::Handle), Self::Error> {
+        let (sound, handle) = self.data.into_sound()?;
+
+        Ok((Box::new(LowPassSound {
+            sound,
+            gain: self.level.gain(),
+            level: self.level,
+            state: Frame::ZERO,
+        }), handle))
+    }
+}
+
+struct LowPassSound {
+    sound: Box<dyn KiraSound>,
+    level: Arc<OcclusionLevel>,
+    /// Gain, which follows the one of the level
+    gain: f64,
+    state: Frame,
+}
+
+impl KiraSound for LowPassSound {
+    fn output_destination(&mut self) -> OutputDestination {
+        self.sound.output_destination()
+    }
+
+    fn on_start_processing(&mut self) {
+        self.sound.on_start_processing();
+    }
+
+    fn process(
+        &mut self,
+        dt: f64,
+        clock_info_provider: &ClockInfoProvider,
+        modulator_value_provider: &ModulatorValueProvider,
+    ) -> Frame {
+        let input = self.sound.process(dt, clock_info_provider, modulator_value_provider);
+        let cutoff = self.level.cutoff();
+        self.gain += (self.level.gain() - self.gain) * (dt / GAIN_RAMP).min(1.0);
+
+        if cutoff >= BYPASS_CUTOFF {
+            self.state = input;
+        } else {
+            let alpha = (1.0 - (-TAU * cutoff * dt).exp()) as f32;
+            self.state = self.state + (input - self.state) * alpha;
+        }
+
+        self.state * self.gain as f32
+    }
+
+    fn finished(&self) -> bool {
+        self.sound.finished()
+    }
+}
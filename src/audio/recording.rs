@@ -0,0 +1,223 @@
+use std::path::Path;
+use std::time::Duration;
+
+pub use kira::dsp::Frame;
+
+use crate::error::FlatboxResult;
+use crate::audio::{
+    AudioManager,
+    error::AudioError,
+    backend::MOCK_SAMPLE_RATE,
+};
+
+/// Mixed stereo output, which is rendered by the offline backend
+///
+/// # Usage example
+///
+/// ```rust
+/// let mut app = Flatbox::init(WindowBuilder {
+///     audio_backend: AudioBackend::Offline,
+///     ..Default::default()
+/// });
+/// // ...
+/// let mut recording = AudioRecording::default();
+/// asset_manager.audio.play(explosion)?;
+///
+/// // Render audio of every captured frame
+/// recording.append(asset_manager.audio.render(time.delta_time())?)?;
+/// // ...
+/// recording.save_wav("trailer.wav")?;
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct AudioRecording {
+    pub sample_rate: u32,
+    pub frames: Vec<Frame>,
+}
+
+impl AudioRecording {
+    pub fn new(sample_rate: u32) -> Self {
+        AudioRecording {
+            sample_rate,
+            frames: vec![],
+        }
+    }
+
+    pub fn duration(&self) -> Duration {
+        Duration::from_secs_f64(self.frames.len() as f64 / self.sample_rate as f64)
+    }
+
+    /// Append frames of `other`, which must have the same sample rate
+    pub fn append(&mut self, mut other: AudioRecording) -> FlatboxResult<()> {
+        if other.sample_rate != self.sample_rate {
+            return Err(AudioError::SampleRateMismatch(self.sample_rate, other.sample_rate).into());
+        }
+
+        self.frames.append(&mut other.frames);
+        Ok(())
+    }
+
+    /// Maximal absolute sample of both channels
+    pub fn peak(&self) -> f32 {
+        self.frames.iter()
+            .map(|frame| frame.left.abs().max(frame.right.abs()))
+            .fold(0.0, f32::max)
+    }
+
+    /// Root mean square of both channels. Use it to compare loudness
+    pub fn rms(&self) -> f32 {
+        if self.frames.is_empty() {
+            return 0.0;
+        }
+
+        let sum = self.frames.iter()
+            .map(|frame| (frame.left * frame.left + frame.right * frame.right) as f64)
+            .sum::<f64>();
+
+        (sum / (self.frames.len() * 2) as f64).sqrt() as f32
+    }
+
+    /// Encode as 16-bit stereo PCM WAV file
+    pub fn to_wav(&self) -> Vec<u8> {
+        const CHANNELS: u16 = 2;
+        const BITS_PER_SAMPLE: u16 = 16;
+
+        let block_align = CHANNELS * BITS_PER_SAMPLE / 8;
+        let data_size = (self.frames.len() * block_align as usize) as u32;
+
+        let mut wav = Vec::with_capacity(44 + data_size as usize);
+        wav.extend_from_slice(b"RIFF");
+        wav.extend_from_slice(&(36 + data_size).to_le_bytes());
+        wav.extend_from_slice(b"WAVE");
+
+        wav.extend_from_slice(b"fmt ");
+        wav.extend_from_slice(&16u32.to_le_bytes());
+        wav.extend_from_slice(&1u16.to_le_bytes());
+        wav.extend_from_slice(&CHANNELS.to_le_bytes());
+        wav.extend_from_slice(&self.sample_rate.to_le_bytes());
+        wav.extend_from_slice(&(self.sample_rate * block_align as u32).to_le_bytes());
+        wav.extend_from_slice(&block_align.to_le_bytes());
+        wav.extend_from_slice(&BITS_PER_SAMPLE.to_le_bytes());
+
+        wav.extend_from_slice(b"data");
+        wav.extend_from_slice(&data_size.to_le_bytes());
+        for frame in &self.frames {
+            for sample in [frame.left, frame.right] {
+                wav.extend_from_slice(&((sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16).to_le_bytes());
+            }
+        }
+
+        wav
+    }
+
+    pub fn save_wav<P: AsRef<Path>>(&self, path: P) -> FlatboxResult<()> {
+        std::fs::write(path, self.to_wav()).map_err(AudioError::from)?;
+        Ok(())
+    }
+}
+
+impl Default for AudioRecording {
+    fn default() -> Self {
+        AudioRecording::new(MOCK_SAMPLE_RATE)
+    }
+}
+
+impl AudioManager {
+    /// Process the `duration` of audio and return the mixed output. It's available with
+    /// [`AudioBackend::Offline`](super::AudioBackend::Offline) only, which doesn't advance
+    /// in real time, so the output is the same for the same session
+    pub fn render(&self, duration: Duration) -> FlatboxResult<AudioRecording> {
+        let frames = self.inner()
+            .render(duration.as_secs_f64())
+            .ok_or(AudioError::BackendError(String::from("rendering requires the offline backend")))?;
+
+        Ok(AudioRecording {
+            sample_rate: MOCK_SAMPLE_RATE,
+            frames,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::audio::AudioBackend;
+
+    use super::*;
+
+    fn recording(frames: &[(f32, f32)]) -> AudioRecording {
+        AudioRecording {
+            sample_rate: 44_100,
+            frames: frames.iter().map(|&(left, right)| Frame { left, right }).collect(),
+        }
+    }
+
+    fn read_u32(wav: &[u8], offset: usize) -> u32 {
+        u32::from_le_bytes(wav[offset..offset + 4].try_into().unwrap())
+    }
+
+    fn read_i16(wav: &[u8], offset: usize) -> i16 {
+        i16::from_le_bytes(wav[offset..offset + 2].try_into().unwrap())
+    }
+
+    #[test]
+    fn wav_header() {
+        let wav = recording(&[(0.0, 0.0); 3]).to_wav();
+
+        assert_eq!(wav.len(), 44 + 3 * 4);
+        assert_eq!(&wav[0..4], b"RIFF");
+        assert_eq!(read_u32(&wav, 4), 36 + 3 * 4);
+        assert_eq!(&wav[8..16], b"WAVEfmt ");
+        assert_eq!(read_u32(&wav, 16), 16);
+        assert_eq!(read_i16(&wav, 20), 1);
+        assert_eq!(read_i16(&wav, 22), 2);
+        assert_eq!(read_u32(&wav, 24), 44_100);
+        assert_eq!(read_u32(&wav, 28), 44_100 * 4);
+        assert_eq!(read_i16(&wav, 32), 4);
+        assert_eq!(read_i16(&wav, 34), 16);
+        assert_eq!(&wav[36..40], b"data");
+        assert_eq!(read_u32(&wav, 40), 3 * 4);
+    }
+
+    #[test]
+    fn wav_samples_are_clamped() {
+        let wav = recording(&[(0.5, -2.0)]).to_wav();
+
+        assert_eq!(read_i16(&wav, 44), (0.5 * i16::MAX as f32) as i16);
+        assert_eq!(read_i16(&wav, 46), -i16::MAX);
+    }
+
+    #[test]
+    fn peak_and_rms() {
+        let recording = recording(&[(0.5, -1.0), (0.0, 0.5)]);
+
+        assert_eq!(recording.peak(), 1.0);
+        assert!((recording.rms() - (1.5f32 / 4.0).sqrt()).abs() < 1e-6);
+        assert_eq!(AudioRecording::default().rms(), 0.0);
+        assert_eq!(AudioRecording::default().peak(), 0.0);
+    }
+
+    #[test]
+    fn append_requires_same_sample_rate() {
+        let mut first = recording(&[(0.5, 0.5)]);
+        first.append(recording(&[(1.0, -1.0)])).unwrap();
+        assert_eq!(first.frames.len(), 2);
+
+        assert!(first.append(AudioRecording::new(48_000)).is_err());
+        assert_eq!(first.frames.len(), 2);
+    }
+
+    #[test]
+    fn render_length() {
+        let audio = AudioManager::with_backend(1, 1, AudioBackend::Offline).unwrap();
+        let recording = audio.render(Duration::from_millis(250)).unwrap();
+
+        assert_eq!(recording.sample_rate, MOCK_SAMPLE_RATE);
+        assert_eq!(recording.frames.len(), MOCK_SAMPLE_RATE as usize / 4);
+        assert_eq!(recording.duration(), Duration::from_millis(250));
+    }
+
+    #[test]
+    fn render_requires_offline_backend() {
+        let audio = AudioManager::with_backend(1, 1, AudioBackend::Mock).unwrap();
+        assert!(audio.render(Duration::from_millis(10)).is_err());
+    }
+}
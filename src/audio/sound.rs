@@ -1,11 +1,17 @@
 use std::fmt;
-use std::path::PathBuf;
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use kira::{
-    sound::static_sound::{StaticSoundData, StaticSoundSettings}, 
+    sound::{
+        FromFileError,
+        static_sound::StaticSoundData, 
+        streaming::StreamingSoundData,
+    },
     spatial::emitter::EmitterId
 };
 use serde::{
-    Serialize, Deserialize, 
+    Serialize, Serializer, Deserialize, Deserializer,
     de::{
         Visitor,
         SeqAccess,
@@ -15,50 +21,297 @@ use serde::{
 };
 
 use crate::error::FlatboxResult;
+use crate::assets::{Vfs, AssetMeta, AssetSource, SoundMeta};
 
 use super::{
     AudioError, 
-    cast::AudioCast
+    cast::AudioCast,
+    mixer::Bus,
+    synth::{SynthSound, SYNTH_EXTENSION},
 };
 
+/// Origin of the encoded sound data
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub enum SoundSource {
+    /// File, which is read through the [`Vfs`], so it can be in any mounted source
+    File(PathBuf),
+    /// Data in memory, e.g. generated or downloaded. It's serialized along with the sound
+    Memory(
+        #[serde(serialize_with = "serialize_bytes", deserialize_with = "deserialize_bytes")]
+        Arc<[u8]>
+    ),
+    /// Procedurally generated sound
+    Synth(SynthSound),
+}
+
+impl SoundSource {
+    /// Path of the file source
+    pub fn path(&self) -> Option<&Path> {
+        match self {
+            SoundSource::File(path) => Some(path),
+            _ => None,
+        }
+    }
+
+    /// Asset meta of the file or the default one
+    fn meta(&self) -> SoundMeta {
+        match self {
+            SoundSource::File(path) => SoundMeta::load_or_default(path),
+            _ => SoundMeta::default(),
+        }
+    }
+}
+
+impl fmt::Debug for SoundSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SoundSource::File(path) => f.debug_tuple("File").field(path).finish(),
+            SoundSource::Memory(bytes) => write!(f, "Memory({} bytes)", bytes.len()),
+            SoundSource::Synth(synth) => f.debug_tuple("Synth").field(synth).finish(),
+        }
+    }
+}
+
+impl From<PathBuf> for SoundSource {
+    fn from(path: PathBuf) -> Self {
+        SoundSource::File(path)
+    }
+}
+
+fn serialize_bytes<S: Serializer>(bytes: &Arc<[u8]>, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_bytes(bytes)
+}
+
+fn deserialize_bytes<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Arc<[u8]>, D::Error> {
+    struct BytesVisitor;
+
+    impl<'de> Visitor<'de> for BytesVisitor {
+        type Value = Arc<[u8]>;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str("bytes")
+        }
+
+        fn visit_bytes<E: DeError>(self, bytes: &[u8]) -> Result<Arc<[u8]>, E> {
+            Ok(Arc::from(bytes))
+        }
+
+        fn visit_seq<V: SeqAccess<'de>>(self, mut seq: V) -> Result<Arc<[u8]>, V::Error> {
+            let mut bytes = Vec::with_capacity(seq.size_hint().unwrap_or_default());
+            while let Some(byte) = seq.next_element()? {
+                bytes.push(byte);
+            }
+
+            Ok(Arc::from(bytes))
+        }
+    }
+
+    deserializer.deserialize_bytes(BytesVisitor)
+}
+
+/// Source of the deserialized sound. Scenes, saved before sound sources, store the path only
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum SourceOrPath {
+    Source(SoundSource),
+    Path(PathBuf),
+}
+
+impl From<SourceOrPath> for SoundSource {
+    fn from(source: SourceOrPath) -> Self {
+        match source {
+            SourceOrPath::Source(source) => source,
+            SourceOrPath::Path(path) => SoundSource::File(path),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize)]
 pub struct Sound {
-    pub(crate) path: PathBuf,
+    pub(crate) source: SoundSource,
+    /// Mixer bus of the sound, when it's not played by an [`AudioCast`]
+    pub bus: Bus,
+    /// Streamed sound is decoded while playing instead of loading. Use it for long music tracks
+    pub(crate) streaming: bool,
 
     #[serde(skip_serializing)]
     pub(crate) cast_id: Option<EmitterId>,
     #[serde(skip_serializing)]
-    pub(crate) static_data: StaticSoundData,
+    pub(crate) static_data: Option<StaticSoundData>,
 }
 
 impl Sound {
-    pub fn new_from_file(path: &'static str) -> FlatboxResult<Self> {
-        let static_data = StaticSoundData::from_file(
-            path, 
-            StaticSoundSettings::default()
-        ).map_err(|e| AudioError::from(e))?;
+    pub fn new_from_file<P: AsRef<Path>>(path: P) -> FlatboxResult<Self> {
+        Sound::new_from_source(SoundSource::File(path.as_ref().into()))
+    }
+
+    /// Create sound from the encoded data, e.g. generated or downloaded one
+    pub fn new_from_bytes(bytes: impl Into<Arc<[u8]>>) -> FlatboxResult<Self> {
+        Sound::new_from_source(SoundSource::Memory(bytes.into()))
+    }
+
+    /// Read the file from the asset source, which is not mounted to the [`Vfs`]. The sound
+    /// is serialized with its data, so mount the source to reference the file instead
+    pub fn new_from_asset_source<P: AsRef<Path>>(source: &dyn AssetSource, path: P) -> FlatboxResult<Self> {
+        let bytes = source.read(path.as_ref()).map_err(AudioError::from)?;
+        Sound::new_from_bytes(bytes)
+    }
+
+    pub fn new_synth(synth: SynthSound) -> Self {
+        Sound {
+            static_data: Some(synth.generate()),
+            source: SoundSource::Synth(synth),
+            bus: Bus::default(),
+            streaming: false,
+            cast_id: None,
+        }
+    }
+
+    pub fn new_from_source(source: SoundSource) -> FlatboxResult<Self> {
+        let static_data = Sound::decode(&source)?;
 
         Ok(Sound {
-            path: path.into(),
+            source,
+            bus: Bus::default(),
+            streaming: false,
             cast_id: None,
-            static_data,
+            static_data: Some(static_data),
         })
     }
 
+    /// Create sound, which is streamed from the file on every playback
+    pub fn new_streaming<P: Into<PathBuf>>(path: P) -> Self {
+        Sound {
+            source: SoundSource::File(path.into()),
+            bus: Bus::default(),
+            streaming: true,
+            cast_id: None,
+            static_data: None,
+        }
+    }
+
+    /// Create sound, which data is not loaded yet. It's used during background loading
+    pub(crate) fn new_empty<P: Into<PathBuf>>(path: P) -> Self {
+        Sound {
+            source: SoundSource::File(path.into()),
+            bus: Bus::default(),
+            streaming: false,
+            cast_id: None,
+            static_data: None,
+        }
+    }
+
+    /// Read audio file through the [`Vfs`] and decode it with settings from the asset meta.
+    /// Files with the `.synth` extension are [`SynthSound`] definitions, which are generated
+    pub(crate) fn decode_file<P: AsRef<Path>>(path: P) -> Result<StaticSoundData, AudioError> {
+        let meta = SoundMeta::load_or_default(path.as_ref());
+        let is_synth = path.as_ref().extension().is_some_and(|extension| extension == SYNTH_EXTENSION);
+        let bytes = Vfs::read(path)?;
+
+        if is_synth {
+            let synth: SynthSound = ron::de::from_bytes(&bytes)
+                .map_err(|e| AudioError::SynthError(e.to_string()))?;
+
+            return Ok(synth.generate().with_settings(meta.settings()));
+        }
+
+        Ok(StaticSoundData::from_cursor(
+            Cursor::new(bytes),
+            meta.settings(),
+        )?)
+    }
+
+    pub(crate) fn decode(source: &SoundSource) -> Result<StaticSoundData, AudioError> {
+        match source {
+            SoundSource::File(path) => Sound::decode_file(path),
+            SoundSource::Memory(bytes) => Ok(StaticSoundData::from_cursor(
+                Cursor::new(bytes.clone()),
+                SoundMeta::default().settings(),
+            )?),
+            SoundSource::Synth(synth) => Ok(synth.generate()),
+        }
+    }
+
+    /// Open stream of the source with settings from the asset meta. Files of the mounted
+    /// sources are read into memory as is, others are read from disk while playing
+    pub(crate) fn open_stream(&self) -> Result<StreamingSoundData<FromFileError>, AudioError> {
+        let settings = self.source.meta().streaming_settings();
+
+        match &self.source {
+            SoundSource::File(path) if !Vfs::is_mounted(path) && path.is_file() => {
+                Ok(StreamingSoundData::from_file(path, settings)?)
+            },
+            SoundSource::File(path) => Ok(StreamingSoundData::from_cursor(Cursor::new(Vfs::read(path)?), settings)?),
+            SoundSource::Memory(bytes) => Ok(StreamingSoundData::from_cursor(Cursor::new(bytes.clone()), settings)?),
+            SoundSource::Synth(_) => Err(AudioError::PlaySoundError(String::from("Synthesized sounds can't be streamed"))),
+        }
+    }
+
+    pub fn source(&self) -> &SoundSource {
+        &self.source
+    }
+
+    /// Path of the sound file. Sounds from memory don't have it
+    pub fn path(&self) -> Option<&Path> {
+        self.source.path()
+    }
+
+    /// Check whether sound data is loaded. Streamed sounds are always ready to play
+    pub fn is_loaded(&self) -> bool {
+        self.streaming || self.static_data.is_some()
+    }
+
+    pub fn is_streaming(&self) -> bool {
+        self.streaming
+    }
+
+    pub(crate) fn set_data(&mut self, static_data: StaticSoundData) {
+        let mut settings = static_data.settings;
+        if let Some(id) = self.cast_id {
+            settings.output_destination = id.into();
+        }
+
+        self.static_data = Some(static_data.with_settings(settings));
+    }
+
     pub(crate) fn set_cast(&mut self, cast: &AudioCast) {
-        let id = Some(cast.handle.id());
+        let Some(handle) = cast.emitter() else {
+            return;
+        };
+
+        let id = Some(handle.id());
         if self.cast_id == id {
             return;
         }
 
-        let settings = StaticSoundSettings::new().output_destination(&cast.handle);
-        let new_data = self.static_data.clone().with_settings(settings);
-
-        self.static_data = new_data;
+        if let Some(static_data) = &self.static_data {
+            let mut settings = static_data.settings;
+            settings.output_destination = handle.into();
+            self.static_data = Some(static_data.with_settings(settings));
+        }
+        
         self.cast_id = id;
     }
 }
 
+impl Sound {
+    /// Recreate deserialized sound. Static sounds are decoded again
+    fn restore(source: SoundSource, bus: Bus, streaming: bool) -> Result<Self, AudioError> {
+        let static_data = match streaming {
+            true => None,
+            false => Some(Sound::decode(&source)?),
+        };
+
+        Ok(Sound {
+            source,
+            bus,
+            streaming,
+            cast_id: None,
+            static_data,
+        })
+    }
+}
+
 impl<'de> Deserialize<'de> for Sound {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
@@ -66,7 +319,7 @@ impl<'de> Deserialize<'de> for Sound {
     {
         #[derive(Deserialize)]
         #[serde(field_identifier, rename_all = "lowercase")]
-        enum SoundField { Path }
+        enum SoundField { Source, Path, Bus, Streaming }
 
         struct SoundVisitor;
 
@@ -81,51 +334,60 @@ impl<'de> Deserialize<'de> for Sound {
             where
                 V: SeqAccess<'de>,
             {
-                let path: PathBuf = seq.next_element()?.ok_or_else(|| DeError::invalid_length(0, &self))?;
-
-                let static_data = StaticSoundData::from_file(
-                    path.clone(), 
-                    StaticSoundSettings::default()
-                ).map_err(|e| AudioError::from(e)).expect("Cannot deserialize audio with path");
-
-                Ok(Sound {
-                    path,
-                    cast_id: None,
-                    static_data,
-                })
+                let source: SourceOrPath = seq.next_element()?.ok_or_else(|| DeError::invalid_length(0, &self))?;
+                let bus: Bus = seq.next_element()?.unwrap_or_default();
+                let streaming: bool = seq.next_element()?.unwrap_or_default();
+
+                Sound::restore(source.into(), bus, streaming).map_err(DeError::custom)
             }
 
             fn visit_map<V>(self, mut map: V) -> Result<Sound, V::Error>
             where
                 V: MapAccess<'de>,
             {
+                let mut source: Option<SoundSource> = None;
                 let mut path: Option<PathBuf> = None;
+                let mut bus: Option<Bus> = None;
+                let mut streaming: Option<bool> = None;
                 while let Some(key) = map.next_key()? {
                     match key {
+                        SoundField::Source => {
+                            if source.is_some() {
+                                return Err(DeError::duplicate_field("source"));
+                            }
+                            source = Some(map.next_value()?);
+                        },
                         SoundField::Path => {
                             if path.is_some() {
                                 return Err(DeError::duplicate_field("path"));
                             }
                             path = Some(map.next_value()?);
-                        }
+                        },
+                        SoundField::Bus => {
+                            if bus.is_some() {
+                                return Err(DeError::duplicate_field("bus"));
+                            }
+                            bus = Some(map.next_value()?);
+                        },
+                        SoundField::Streaming => {
+                            if streaming.is_some() {
+                                return Err(DeError::duplicate_field("streaming"));
+                            }
+                            streaming = Some(map.next_value()?);
+                        },
                     }
                 }
-                let path = path.ok_or_else(|| DeError::missing_field("path"))?;
-                
-                let static_data = StaticSoundData::from_file(
-                    path.clone(), 
-                    StaticSoundSettings::default()
-                ).map_err(|e| AudioError::from(e)).expect("Cannot deserialize audio with path");
-
-                Ok(Sound {
-                    path,
-                    cast_id: None,
-                    static_data,
-                })
+                let source = source
+                    .or(path.map(SoundSource::File))
+                    .ok_or_else(|| DeError::missing_field("source"))?;
+                let bus = bus.unwrap_or_default();
+                let streaming = streaming.unwrap_or_default();
+
+                Sound::restore(source, bus, streaming).map_err(DeError::custom)
             }
         }
 
-        const FIELDS: &'static [&'static str] = &["path"];
+        const FIELDS: &[&str] = &["source", "path", "bus", "streaming"];
         deserializer.deserialize_struct("Sound", FIELDS, SoundVisitor)
     }
 }
\ No newline at end of file
@@ -0,0 +1,258 @@
+use std::f64::consts::TAU;
+use std::sync::Arc;
+
+use kira::{
+    dsp::Frame,
+    sound::static_sound::{StaticSoundData, StaticSoundSettings},
+};
+use serde::{Serialize, Deserialize};
+
+use crate::audio::volume::Volume;
+
+/// Sample rate of the synthesized sounds
+pub const SYNTH_SAMPLE_RATE: u32 = 48_000;
+
+/// Extension of the files with [`SynthSound`] definitions
+pub const SYNTH_EXTENSION: &str = "synth";
+
+/// Shape of the oscillator wave
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum Waveform {
+    #[default]
+    Sine,
+    Square,
+    Saw,
+    /// White noise, which is sampled once per oscillator period. Higher frequency gives brighter noise
+    Noise,
+}
+
+/// Attack, decay, sustain and release envelope. Times are in seconds
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Envelope {
+    pub attack: f64,
+    pub decay: f64,
+    /// Level, which is held after the decay until the note ends, in range `0.0..=1.0`
+    pub sustain: f64,
+    pub release: f64,
+}
+
+impl Envelope {
+    pub fn new(attack: f64, decay: f64, sustain: f64, release: f64) -> Self {
+        Envelope { attack, decay, sustain, release }
+    }
+
+    /// Level of the held note at the time `t`
+    fn note_level(&self, t: f64) -> f64 {
+        if t < self.attack {
+            t / self.attack
+        } else if t < self.attack + self.decay {
+            1.0 - (1.0 - self.sustain) * (t - self.attack) / self.decay
+        } else {
+            self.sustain
+        }
+    }
+
+    /// Level at the time `t` of the note with the `duration`
+    fn level(&self, t: f64, duration: f64) -> f64 {
+        if t < duration {
+            self.note_level(t)
+        } else if self.release > 0.0 {
+            self.note_level(duration) * (1.0 - (t - duration) / self.release).max(0.0)
+        } else {
+            0.0
+        }
+    }
+}
+
+impl Default for Envelope {
+    fn default() -> Self {
+        Envelope {
+            attack: 0.005,
+            decay: 0.05,
+            sustain: 0.7,
+            release: 0.05,
+        }
+    }
+}
+
+/// One-pole filter of the synthesized sound
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum SynthFilter {
+    /// Remove frequencies above the `cutoff` (in Hz)
+    LowPass { cutoff: f64 },
+    /// Remove frequencies below the `cutoff` (in Hz)
+    HighPass { cutoff: f64 },
+}
+
+/// Definition of the procedurally generated sound. It can be created with
+/// [`AudioManager::create_synth_sound`](super::AudioManager::create_synth_sound)
+/// or loaded from the RON file with the `.synth` extension
+///
+/// # Usage example
+///
+/// ```rust
+/// // assets/sfx/blip.synth
+/// (
+///     waveform: Square,
+///     frequency: 880.0,
+///     sweep_to: Some(1760.0),
+///     duration: 0.08,
+///     filter: Some(LowPass(cutoff: 4000.0)),
+/// )
+/// ```
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SynthSound {
+    pub waveform: Waveform,
+    /// Start frequency in Hz
+    pub frequency: f64,
+    /// End frequency of the exponential sweep
+    pub sweep_to: Option<f64>,
+    /// Length of the note in seconds. The envelope release is played after it
+    pub duration: f64,
+    pub envelope: Envelope,
+    pub filter: Option<SynthFilter>,
+    pub volume: Volume,
+    /// Seed of the noise generator
+    pub seed: u64,
+}
+
+impl SynthSound {
+    pub fn new(waveform: Waveform, frequency: f64, duration: f64) -> Self {
+        SynthSound {
+            waveform,
+            frequency,
+            duration,
+            ..Default::default()
+        }
+    }
+
+    pub fn sweep_to(mut self, frequency: f64) -> Self {
+        self.sweep_to = Some(frequency);
+        self
+    }
+
+    pub fn envelope(mut self, envelope: Envelope) -> Self {
+        self.envelope = envelope;
+        self
+    }
+
+    pub fn filter(mut self, filter: SynthFilter) -> Self {
+        self.filter = Some(filter);
+        self
+    }
+
+    pub fn volume(mut self, volume: Volume) -> Self {
+        self.volume = volume;
+        self
+    }
+
+    /// Length of the sound in seconds, including the release
+    pub fn length(&self) -> f64 {
+        self.duration.max(0.0) + self.envelope.release.max(0.0)
+    }
+
+    /// Generate mono samples with the `sample_rate`
+    pub fn samples(&self, sample_rate: u32) -> Vec<f32> {
+        let dt = 1.0 / sample_rate as f64;
+        let count = (self.length() * sample_rate as f64) as usize;
+        let length = self.length().max(dt);
+
+        let start = self.frequency.max(0.0);
+        let end = self.sweep_to.unwrap_or(start).max(0.0);
+        let amplitude = self.volume.amplitude();
+
+        let mut seed = self.seed | 1;
+        let mut noise = 0.0;
+        let mut phase = 0.0;
+        let mut filter = FilterState::default();
+
+        (0..count).map(|i| {
+            let t = i as f64 * dt;
+
+            let frequency = if start > 0.0 && end > 0.0 {
+                start * (end / start).powf(t / length)
+            } else {
+                start + (end - start) * t / length
+            };
+
+            let value = match self.waveform {
+                Waveform::Sine => (phase * TAU).sin(),
+                Waveform::Square => if phase < 0.5 { 1.0 } else { -1.0 },
+                Waveform::Saw => 2.0 * phase - 1.0,
+                Waveform::Noise => noise,
+            };
+
+            phase += frequency * dt;
+            if phase >= 1.0 || i == 0 {
+                phase = phase.fract();
+
+                seed ^= seed << 13;
+                seed ^= seed >> 7;
+                seed ^= seed << 17;
+                noise = (seed >> 11) as f64 / (1u64 << 53) as f64 * 2.0 - 1.0;
+            }
+
+            let value = match self.filter {
+                Some(f) => filter.apply(f, value, dt),
+                None => value,
+            };
+
+            (value * self.envelope.level(t, self.duration) * amplitude) as f32
+        }).collect()
+    }
+
+    /// Generate the sound data, which can be played by kira
+    pub fn generate(&self) -> StaticSoundData {
+        let frames = self.samples(SYNTH_SAMPLE_RATE)
+            .into_iter()
+            .map(Frame::from_mono)
+            .collect::<Vec<_>>();
+
+        StaticSoundData {
+            sample_rate: SYNTH_SAMPLE_RATE,
+            frames: Arc::from(frames),
+            settings: StaticSoundSettings::default(),
+        }
+    }
+}
+
+impl Default for SynthSound {
+    fn default() -> Self {
+        SynthSound {
+            waveform: Waveform::Sine,
+            frequency: 440.0,
+            sweep_to: None,
+            duration: 0.2,
+            envelope: Envelope::default(),
+            filter: None,
+            volume: Volume::default(),
+            seed: 0,
+        }
+    }
+}
+
+#[derive(Default)]
+struct FilterState {
+    input: f64,
+    output: f64,
+}
+
+impl FilterState {
+    fn apply(&mut self, filter: SynthFilter, input: f64, dt: f64) -> f64 {
+        self.output = match filter {
+            SynthFilter::LowPass { cutoff } => {
+                let rc = 1.0 / (TAU * cutoff.max(1.0));
+                self.output + dt / (rc + dt) * (input - self.output)
+            },
+            SynthFilter::HighPass { cutoff } => {
+                let rc = 1.0 / (TAU * cutoff.max(1.0));
+                rc / (rc + dt) * (self.output + input - self.input)
+            },
+        };
+        self.input = input;
+
+        self.output
+    }
+}
@@ -19,6 +19,16 @@ impl Volume {
         Volume(min(value.into(), 100))
     }
     
+    /// Get volume value in range `0..=100`
+    pub fn get(&self) -> u8 {
+        self.0
+    }
+
+    /// Convert to the linear amplitude, where `100` is `1.0`
+    pub fn amplitude(&self) -> f64 {
+        self.0 as f64 / 100.0
+    }
+
     pub fn set_volume<U>(&mut self, value: U)
     where
         U: Into<u8>
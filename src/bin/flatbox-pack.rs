@@ -0,0 +1,128 @@
+//!
+//! # flatbox-pack
+//!
+//! Tool for building and inspecting compressed asset packs,
+//! which can be mounted with [`ArchiveSource`](flatbox::assets::ArchiveSource)
+//!
+//! ```sh
+//! flatbox-pack build assets -o assets.tar.lz4
+//! flatbox-pack list assets.tar.lz4
+//! flatbox-pack verify assets.tar.lz4
+//! flatbox-pack extract assets.tar.lz4 unpacked
+//! flatbox-pack meta assets
+//! ```
+//!
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use flatbox::assets::{AssetPack, PackManifest, generate_meta};
+use flatbox::error::FlatboxResult;
+
+const USAGE: &str = "\
+Usage:
+    flatbox-pack build <DIRECTORY> [-o <OUTPUT>]   Pack directory (default output is <DIRECTORY>.tar.lz4)
+    flatbox-pack list <PACK>                       List packed files
+    flatbox-pack extract <PACK> [DIRECTORY]        Extract packed files (default directory is current)
+    flatbox-pack verify <PACK>                     Check packed files against the manifest
+    flatbox-pack meta <DIRECTORY>                  Create missing .meta files of textures and sounds";
+
+fn main() -> ExitCode {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    let args = args.iter().map(|a| a.as_str()).collect::<Vec<_>>();
+
+    let result = match args.as_slice() {
+        ["build", directory] => build(directory, None),
+        ["build", directory, "-o", output] => build(directory, Some(output)),
+        ["list", pack] => list(pack),
+        ["extract", pack] => extract(pack, "."),
+        ["extract", pack, directory] => extract(pack, directory),
+        ["verify", pack] => verify(pack),
+        ["meta", directory] => meta(directory),
+        ["help"] | ["-h"] | ["--help"] => {
+            println!("{USAGE}");
+            return ExitCode::SUCCESS;
+        },
+        _ => {
+            eprintln!("{USAGE}");
+            return ExitCode::FAILURE;
+        },
+    };
+
+    match result {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(error) => {
+            eprintln!("Error: {error}");
+            ExitCode::FAILURE
+        },
+    }
+}
+
+fn build(directory: &str, output: Option<&str>) -> FlatboxResult<bool> {
+    let output = match output {
+        Some(output) => PathBuf::from(output),
+        None => {
+            let name = PathBuf::from(directory)
+                .file_name()
+                .map(|n| n.to_string_lossy().into_owned())
+                .unwrap_or(String::from("assets"));
+
+            PathBuf::from(format!("{name}.tar.lz4"))
+        },
+    };
+
+    let manifest = AssetPack::build(directory, &output)?;
+    println!(
+        "Packed {} files ({} bytes) to '{}'",
+        manifest.files.len(),
+        manifest.total_size(),
+        output.display(),
+    );
+
+    Ok(true)
+}
+
+fn list(pack: &str) -> FlatboxResult<bool> {
+    print_manifest(&AssetPack::manifest(pack)?);
+    Ok(true)
+}
+
+fn extract(pack: &str, directory: &str) -> FlatboxResult<bool> {
+    let manifest = AssetPack::extract(pack, directory)?;
+    println!("Extracted {} files to '{directory}'", manifest.files.len());
+    Ok(true)
+}
+
+fn verify(pack: &str) -> FlatboxResult<bool> {
+    let issues = AssetPack::verify(pack)?;
+
+    for issue in &issues {
+        eprintln!("{issue}");
+    }
+
+    if issues.is_empty() {
+        println!("Pack '{pack}' is valid");
+    }
+
+    Ok(issues.is_empty())
+}
+
+fn meta(directory: &str) -> FlatboxResult<bool> {
+    let created = generate_meta(directory)?;
+
+    for path in &created {
+        println!("Created '{}'", path.display());
+    }
+
+    println!("Created {} meta files", created.len());
+    Ok(true)
+}
+
+fn print_manifest(manifest: &PackManifest) {
+    for entry in &manifest.files {
+        println!("{:08x} {:>12} {}", entry.crc32, entry.size, entry.path.display());
+    }
+
+    println!("{} files, {} bytes", manifest.files.len(), manifest.total_size());
+}
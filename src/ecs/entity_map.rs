@@ -0,0 +1,253 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use hecs::Entity;
+
+/// Mapping of the entity references, which have been saved (e.g. in [`Scene`](crate::assets::Scene)
+/// or save file), to the newly spawned entities
+#[derive(Clone, Debug, Default)]
+pub struct EntityMap {
+    map: HashMap<Entity, Entity>,
+    invalidate_unknown: bool,
+}
+
+/// Generation, reserved for the scene-local ids. Live entities start from generation 1,
+/// so they never collide with the local ones
+const LOCAL_GENERATION: u64 = u32::MAX as u64;
+
+impl EntityMap {
+    pub fn new() -> Self {
+        EntityMap::default()
+    }
+
+    /// Create mapping, which replaces references to unknown entities with [`EntityMap::invalid`]
+    /// instead of leaving them as is. Used when capturing entities into a scene
+    pub fn strict() -> Self {
+        EntityMap {
+            invalidate_unknown: true,
+            ..Default::default()
+        }
+    }
+
+    /// Scene-local reference of the entity with given index in the scene. Use it
+    /// to refer to other entities, when creating scenes manually
+    pub fn local(index: u32) -> Entity {
+        Entity::from_bits(LOCAL_GENERATION << 32 | index as u64).unwrap()
+    }
+
+    /// Reference, which doesn't point to any entity. References to the entities outside
+    /// of the scene or save file are written as this one
+    pub fn invalid() -> Entity {
+        Entity::from_bits(u64::MAX).unwrap()
+    }
+
+    /// Whether the `entity` is a scene-local reference
+    pub fn is_local(entity: Entity) -> bool {
+        entity.to_bits().get() >> 32 == LOCAL_GENERATION && entity != EntityMap::invalid()
+    }
+
+    pub fn insert(&mut self, saved: Entity, spawned: Entity) {
+        self.map.insert(saved, spawned);
+    }
+
+    pub fn get(&self, saved: Entity) -> Option<Entity> {
+        self.map.get(&saved).copied()
+    }
+
+    /// Rewrite saved entity reference. References to unknown entities are left as is,
+    /// or replaced with [`EntityMap::invalid`] if the map is [`strict`](#method.strict)
+    pub fn map(&self, entity: &mut Entity) {
+        match self.get(*entity) {
+            Some(spawned) => *entity = spawned,
+            None if self.invalidate_unknown => *entity = EntityMap::invalid(),
+            None => {},
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+}
+
+/// Trait for components, which store references to other entities. References
+/// are rewritten, when the component is loaded from scene or save file
+///
+/// # Usage example
+///
+/// ```rust
+/// #[derive(Clone, Serialize, Deserialize)]
+/// struct Target {
+///     #[serde(with = "entity_ref")]
+///     entity: Entity,
+/// }
+///
+/// impl MapEntities for Target {
+///     fn map_entities(&mut self, entity_map: &EntityMap) {
+///         entity_map.map(&mut self.entity);
+///     }
+/// }
+///
+/// impl_ser_component!(map_entities: Target);
+/// ```
+pub trait MapEntities {
+    fn map_entities(&mut self, entity_map: &EntityMap);
+}
+
+impl MapEntities for Entity {
+    fn map_entities(&mut self, entity_map: &EntityMap) {
+        entity_map.map(self);
+    }
+}
+
+impl<T: MapEntities> MapEntities for Option<T> {
+    fn map_entities(&mut self, entity_map: &EntityMap) {
+        if let Some(value) = self {
+            value.map_entities(entity_map);
+        }
+    }
+}
+
+impl<T: MapEntities> MapEntities for Vec<T> {
+    fn map_entities(&mut self, entity_map: &EntityMap) {
+        for value in self {
+            value.map_entities(entity_map);
+        }
+    }
+}
+
+thread_local! {
+    static LOCAL_ENTITIES: RefCell<Option<HashMap<Entity, Entity>>> = const { RefCell::new(None) };
+}
+
+/// Scope, during which entity references, serialized with [`entity_ref`], are written as
+/// scene-local ids. Entities, which are missing in the scope, are written as invalid ones
+pub struct LocalEntityScope(());
+
+impl LocalEntityScope {
+    /// Start scope, where the entities are numbered in the given order
+    pub fn new(entities: impl IntoIterator<Item = Entity>) -> Self {
+        let map = entities
+            .into_iter()
+            .enumerate()
+            .map(|(i, entity)| (entity, EntityMap::local(i as u32)))
+            .collect();
+
+        LOCAL_ENTITIES.with(|local| *local.borrow_mut() = Some(map));
+        LocalEntityScope(())
+    }
+}
+
+impl Drop for LocalEntityScope {
+    fn drop(&mut self) {
+        LOCAL_ENTITIES.with(|local| *local.borrow_mut() = None);
+    }
+}
+
+/// Serde helper for the entity references. Use it with `#[serde(with = "entity_ref")]`
+pub mod entity_ref {
+    use hecs::Entity;
+    use serde::{Serialize, Deserialize, Serializer, Deserializer, de::Error as DeError};
+
+    use super::{EntityMap, LOCAL_ENTITIES};
+
+    pub fn serialize<S: Serializer>(entity: &Entity, serializer: S) -> Result<S::Ok, S::Error> {
+        let entity = LOCAL_ENTITIES.with(|local| match &*local.borrow() {
+            Some(map) => map.get(entity).copied().unwrap_or_else(EntityMap::invalid).to_bits().get(),
+            None => entity.to_bits().get(),
+        });
+
+        entity.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Entity, D::Error> {
+        let bits = u64::deserialize(deserializer)?;
+        Entity::from_bits(bits).ok_or_else(|| DeError::custom(format!("Invalid entity {bits}")))
+    }
+
+    /// Serde helper for optional entity references. Use it with `#[serde(with = "entity_ref::option")]`
+    pub mod option {
+        use hecs::Entity;
+        use serde::{Serialize, Deserialize, Serializer, Deserializer};
+
+        pub fn serialize<S: Serializer>(entity: &Option<Entity>, serializer: S) -> Result<S::Ok, S::Error> {
+            struct Wrapper<'a>(&'a Entity);
+
+            impl Serialize for Wrapper<'_> {
+                fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                    super::serialize(self.0, serializer)
+                }
+            }
+
+            entity.as_ref().map(Wrapper).serialize(serializer)
+        }
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Entity>, D::Error> {
+            #[derive(Deserialize)]
+            struct Wrapper(#[serde(with = "super")] Entity);
+
+            Ok(Option::<Wrapper>::deserialize(deserializer)?.map(|w| w.0))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use hecs::World;
+
+    use super::*;
+
+    #[test]
+    fn local_ids_differ_from_real_ones() {
+        let mut world = World::new();
+        let real = (0..4).map(|_| world.spawn(())).collect::<Vec<_>>();
+
+        for (i, &entity) in real.iter().enumerate() {
+            let local = EntityMap::local(i as u32);
+
+            assert_ne!(local, entity);
+            assert_eq!(local.id(), entity.id());
+            assert!(EntityMap::is_local(local));
+            assert!(!EntityMap::is_local(entity));
+        }
+
+        assert!(!EntityMap::is_local(EntityMap::invalid()));
+    }
+
+    #[test]
+    fn map_local_ids() {
+        let mut world = World::new();
+        let spawned = world.spawn(());
+
+        let mut entity_map = EntityMap::new();
+        entity_map.insert(EntityMap::local(0), spawned);
+
+        let mut entity = EntityMap::local(0);
+        entity_map.map(&mut entity);
+        assert_eq!(entity, spawned);
+
+        let mut unknown = EntityMap::local(1);
+        entity_map.map(&mut unknown);
+        assert_eq!(unknown, EntityMap::local(1));
+    }
+
+    #[test]
+    fn strict_map_invalidates_unknown() {
+        let mut world = World::new();
+        let captured = world.spawn(());
+        let outside = world.spawn(());
+
+        let mut entity_map = EntityMap::strict();
+        entity_map.insert(captured, EntityMap::local(0));
+
+        let (mut inside, mut outside) = (captured, outside);
+        entity_map.map(&mut inside);
+        entity_map.map(&mut outside);
+
+        assert_eq!(inside, EntityMap::local(0));
+        assert_eq!(outside, EntityMap::invalid());
+    }
+}
@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::any::TypeId;
 use std::sync::Arc;
 
@@ -15,7 +15,7 @@ impl<E: Clone + Send + Sync + 'static> Event for E {}
 
 /// Routine, which reads and writes events of a concrete type
 pub struct EventHandler<E: Event> {
-    events: Option<E>,
+    events: Vec<E>,
 }
 
 impl<E: Event> EventHandler<E> {
@@ -26,36 +26,46 @@ impl<E: Event> EventHandler<E> {
     
     /// Send event to the handler
     pub fn send(&mut self, event: E){        
-        self.events = Some(event);
+        self.events.push(event);
     }
     
-    /// Listen for events
+    /// Listen for the latest event of the current frame
     pub fn read(&self) -> Option<E> {
-        self.events.clone()
+        self.events.last().cloned()
+    }
+
+    /// Iterate over all events, sent during the current frame
+    pub fn iter(&self) -> impl Iterator<Item = &E> {
+        self.events.iter()
     }
     
-    /// Clear events. It is called by the engine at every schedule run
+    /// Clear events. It is called by the engine at the beginning of every frame
     pub fn clear(&mut self){
-        self.events = None;
+        self.events.clear();
     }
 }
 
 impl<E: Event> Default for EventHandler<E> {
     fn default() -> Self {
-        EventHandler { events: None }
+        EventHandler { events: vec![] }
     }
 }
 
-pub trait GenericEventHandler: AsAny + Send + Sync + 'static {}
-impl<E: Event> GenericEventHandler for EventHandler<E> {} 
-// TODO: Extend `GenericEventHandler` trait:
-// - fn send<E: Event>(&mut self, event: E)
-// - fn clear(&mut self)
-// - fn read(&self) -> Option<E>
+pub trait GenericEventHandler: AsAny + Send + Sync + 'static {
+    /// Clear events of the handler
+    fn clear(&mut self);
+}
+
+impl<E: Event> GenericEventHandler for EventHandler<E> {
+    fn clear(&mut self) {
+        EventHandler::clear(self);
+    }
+}
 
 #[derive(Default)]
 pub struct Events {
     storage: HashMap<TypeId, Arc<RwLock<dyn GenericEventHandler>>>,
+    persistent: HashSet<TypeId>,
 }
 
 impl Events {
@@ -103,4 +113,29 @@ impl Events {
             self.storage.insert(TypeId::of::<H>(), Arc::new(RwLock::new(handler)));
         }
     }
+
+    /// Push handler, which isn't cleared every frame, e.g. the one of `GuiContext`,
+    /// which is updated by the renderer itself
+    pub fn push_persistent_handler<H: GenericEventHandler>(
+        &mut self,
+        handler: H,
+    ){
+        self.push_handler(handler);
+        self.persistent.insert(TypeId::of::<H>());
+    }
+
+    /// Clear all handlers except the persistent ones. It is called by the engine
+    /// at the beginning of every frame, so events live for one frame only
+    pub fn clear(&self) {
+        for (id, handler) in &self.storage {
+            if self.persistent.contains(id) {
+                continue;
+            }
+
+            match handler.try_write() {
+                Some(mut handler) => handler.clear(),
+                None => log::error!("Cannot clear locked event handler"),
+            }
+        }
+    }
 }
\ No newline at end of file
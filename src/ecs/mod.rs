@@ -1,6 +1,7 @@
 pub mod event;
 pub mod systems;
 pub mod runners;
+pub mod entity_map;
 
 pub use hecs_schedule::{
     *,
@@ -42,5 +43,6 @@ pub use hecs::{
 pub use event::*;
 pub use systems::*;
 pub use runners::*;
+pub use entity_map::*;
 
 pub type Schedules = std::collections::HashMap<&'static str, ScheduleBuilder>;
\ No newline at end of file
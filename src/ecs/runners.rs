@@ -18,7 +18,7 @@ pub fn default_runner(flatbox: &mut Flatbox) {
     let mut systems = flatbox.schedules.get_mut("update").unwrap().build();
     
     #[cfg(feature = "egui")]
-    flatbox.events.push_handler(EventHandler::<GuiContext>::new());
+    flatbox.events.push_persistent_handler(EventHandler::<GuiContext>::new());
     flatbox.events.push_handler(EventHandler::<AppExit>::new());
     
     setup_systems.execute((
@@ -60,6 +60,8 @@ pub fn default_runner(flatbox: &mut Flatbox) {
         }
         
         WinitEvent::RedrawRequested(_) => {
+            flatbox.events.clear();
+
             systems.execute((
                 &mut flatbox.world,
                 &mut flatbox.lua_manager,
@@ -70,7 +72,7 @@ pub fn default_runner(flatbox: &mut Flatbox) {
                 &mut flatbox.asset_manager,
             )).expect("Cannot execute loop schedule");
             
-            flatbox.world.clear_trackers(); // TODO: Clear all events (krom GUI)
+            flatbox.world.clear_trackers();
         }
         
         _ => {}
@@ -94,6 +96,8 @@ pub fn default_runner(flatbox: &mut Flatbox) {
     )).expect("Cannot execute setup schedule");
 
     loop {
+        flatbox.events.clear();
+
         systems.execute((
             &mut flatbox.world,
             &mut flatbox.lua_manager,
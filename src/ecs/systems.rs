@@ -29,6 +29,9 @@
 #[allow(unused_imports)]
 use crate::Flatbox;
 
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
 #[cfg(feature = "render")]
 use {
     ash::vk,
@@ -55,6 +58,7 @@ use crate::audio::*;
 use crate::time::*;
 use crate::ecs::*;
 use crate::physics::*;
+use crate::scripting::{Script, LuaManager};
 use crate::error::FlatboxResult;
 use crate::math::transform::Transform;
 
@@ -74,7 +78,18 @@ pub fn processing_audio(
     cast_world: SubWorld<(&Transform, &mut AudioCast)>,
     listener_world: SubWorld<(&Transform, &mut AudioListener)>,
     mut asset_manager: Write<AssetManager>,
+    events: Read<Events>,
 ) -> FlatboxResult<()> {
+    asset_manager.audio.update_backend();
+
+    for (_, (t, mut c)) in &mut cast_world.query::<(&Transform, &mut AudioCast)>(){
+        c.restore(&mut asset_manager.audio, t)?;
+    }
+
+    for (_, (t, mut l)) in &mut listener_world.query::<(&Transform, &mut AudioListener)>(){
+        l.restore(&mut asset_manager.audio, t)?;
+    }
+
     for (_, (storage, cast)) in &mut storage_world.query::<(&AudioStorage, &AudioCast)>(){
         for handle in &storage.sounds {
             if let Some(sound) = asset_manager.audio.get_sound_mut(handle.clone()){
@@ -91,13 +106,91 @@ pub fn processing_audio(
         l.set_transform(&t)?;
     }
 
+    asset_manager.audio.update_music()?;
+
+    let finished = asset_manager.audio.collect_finished();
+
+    if let Some(mut handler) = events.get_handler_mut::<SoundFinished>() {
+        for event in finished {
+            handler.send(event);
+        }
+    }
+
+    let beats = asset_manager.audio.collect_beats();
+
+    if let Some(mut handler) = events.get_handler_mut::<Beat>() {
+        for event in beats {
+            handler.send(event);
+        }
+    }
+
+    Ok(())
+}
+
+/// Attenuate and filter sounds of the [`AudioCast`]s, which are occluded from the listeners by
+/// colliders. It's optional, so add it after `processing_audio` to the "update" schedule
+pub fn audio_occlusion(
+    listener_world: SubWorld<(&Transform, &AudioListener)>,
+    cast_world: SubWorld<(&Transform, &mut AudioCast)>,
+    occluder_world: SubWorld<(&BodyHandle, &AudioOccluder)>,
+    body_world: SubWorld<&BodyHandle>,
+    physics_handler: Read<PhysicsHandler>,
+    asset_manager: Read<AssetManager>,
+    time: Read<Time>,
+) -> FlatboxResult<()> {
+    let listeners = listener_world.query::<(&Transform, &AudioListener)>()
+        .iter()
+        .map(|(entity, (t, _))| (t.translation, body_world.get::<BodyHandle>(entity).ok().map(|b| b.1)))
+        .collect::<Vec<_>>();
+
+    if listeners.is_empty() {
+        return Ok(());
+    }
+
+    let occluders = occluder_world.query::<(&BodyHandle, &AudioOccluder)>()
+        .iter()
+        .map(|(_, (body, occluder))| (body.1, occluder.factor))
+        .collect::<HashMap<_, _>>();
+
+    let settings = asset_manager.audio.occlusion;
+    let rate = match settings.smoothing.is_zero() {
+        true => 1.0,
+        false => (time.delta_time().as_secs_f32() / settings.smoothing.as_secs_f32()).min(1.0),
+    };
+
+    for (entity, (t, mut c)) in &mut cast_world.query::<(&Transform, &mut AudioCast)>(){
+        let Some(emitter) = c.emitter().map(|handle| handle.id()) else {
+            continue;
+        };
+
+        let own = body_world.get::<BodyHandle>(entity).ok().map(|b| b.1);
+
+        // The least occluded listener is heard
+        let target = listeners.iter().map(|(position, listener)| {
+            let direction = t.translation - position;
+
+            // Sensors, e.g. trigger volumes, don't occlude sounds
+            physics_handler.intersections_with_ray(*position, direction, direction.norm(), QueryFilter::default().exclude_sensors())
+                .into_iter()
+                .filter(|(collider, _)| Some(*collider) != own && Some(*collider) != *listener)
+                .map(|(collider, _)| occluders.get(&collider).copied().unwrap_or(settings.collider_factor))
+                .sum::<f32>()
+                .min(1.0)
+        }).fold(1.0, f32::min);
+
+        c.occlusion += (target - c.occlusion) * rate;
+        asset_manager.audio.apply_occlusion(emitter, c.occlusion);
+    }
+
     Ok(())
 }
 
+/// Step the physics simulation and send [`Collision`] and [`ContactForce`] events
 pub fn update_physics(
     mut physics_handler: Write<PhysicsHandler>,
     physics_world: SubWorld<(&mut Transform, &BodyHandle)>,
     added_world: SubWorld<(&Transform, &BodyHandle, Added<BodyHandle>)>,
+    events: Read<Events>,
 ) -> FlatboxResult<()> {    
     for (_, (transform, handle, added)) in &mut added_world.query::<(
         &Transform, &BodyHandle, Added<BodyHandle>
@@ -110,24 +203,234 @@ pub fn update_physics(
     }
     
     physics_handler.step();
-    
-    for (_, (mut transform, handle)) in &mut physics_world.query::<(
+
+    for (entity, (mut transform, handle)) in &mut physics_world.query::<(
         &mut Transform, &BodyHandle,
     )>(){
         let rigidbody = physics_handler.rigidbody(*handle)?;
         transform.translation = *rigidbody.translation();
         transform.rotation = *rigidbody.rotation();        
+        physics_handler.events.insert(handle.1, entity);
+    }
+
+    let (collisions, contact_forces) = physics_handler.drain_events();
+
+    if let Some(mut handler) = events.get_handler_mut::<Collision>() {
+        for event in collisions {
+            handler.send(event);
+        }
+    }
+
+    if let Some(mut handler) = events.get_handler_mut::<ContactForce>() {
+        for event in contact_forces {
+            handler.send(event);
+        }
     }
     
     Ok(())
 }
 
+/// Apply assets, which have been loaded in the background, and send [`AssetEvent`]s
+pub fn update_assets(
+    mut asset_manager: Write<AssetManager>,
+    events: Read<Events>,
+){
+    let asset_events = asset_manager.update_loading();
+
+    if let Some(mut handler) = events.get_handler_mut::<AssetEvent>() {
+        for event in asset_events {
+            handler.send(event);
+        }
+    }
+}
+
+/// Reload changed asset files, OBJ models and Lua scripts. It's added by [`HotReload`] extension
+pub fn hot_reload_assets(
+    mut asset_manager: Write<AssetManager>,
+    lua_manager: Read<LuaManager>,
+    script_world: SubWorld<&Script>,
+    #[cfg(feature = "render")]
+    model_world: SubWorld<&mut Model>,
+){
+    if !asset_manager.reload_modified() {
+        return;
+    }
+
+    let mut paths = HashSet::new();
+
+    for (_, script) in &mut script_world.query::<&Script>(){
+        paths.insert(script.path.clone());
+    }
+
+    #[cfg(feature = "render")]
+    for (_, model) in &mut model_world.query::<&Model>(){
+        if let MeshType::Loaded(path) = &model.mesh_type {
+            paths.insert(path.clone());
+        }
+    }
+
+    let modified = match &mut asset_manager.watcher {
+        Some(watcher) => paths.into_iter()
+            .filter(|path| watcher.is_modified(path))
+            .collect::<HashSet<_>>(),
+        None => return,
+    };
+
+    if modified.is_empty() {
+        return;
+    }
+
+    for (_, script) in &mut script_world.query::<&Script>(){
+        if modified.contains(&script.path) {
+            if let Err(error) = lua_manager.execute(script) {
+                log::error!("Cannot execute reloaded script '{}': {error}", script.path.display());
+            }
+        }
+    }
+
+    #[cfg(feature = "render")]
+    for (_, mut model) in &mut model_world.query::<&mut Model>(){
+        let path = match &model.mesh_type {
+            MeshType::Loaded(path) if modified.contains(path) => path.clone(),
+            _ => continue,
+        };
+
+        match Mesh::try_load_obj(&path) {
+            Ok(mut meshes) if !meshes.is_empty() => {
+                let loaded = meshes.swap_remove(0);
+
+                // Keep GPU buffers, they are refilled by `update_models_system`
+                match &mut model.mesh {
+                    Some(mesh) => {
+                        mesh.vertexdata = loaded.vertexdata;
+                        mesh.indexdata = loaded.indexdata;
+                    },
+                    None => model.mesh = Some(loaded),
+                }
+            },
+            Ok(_) => log::error!("Cannot reload model '{}': file has no meshes", path.display()),
+            Err(error) => log::error!("Cannot reload model: {error}"),
+        }
+    }
+
+    for path in modified {
+        asset_manager.push_event(AssetEvent::FileModified(path));
+    }
+}
+
+/// Respawn instances of the changed scenes, added with [`AssetWatcher::watch_scene`].
+/// It's added by [`HotReload`] extension
+pub fn hot_reload_scenes(
+    mut world: Write<World>,
+    mut asset_manager: Write<AssetManager>,
+){
+    let outdated = match &mut asset_manager.watcher {
+        Some(watcher) => watcher.take_outdated_scenes(),
+        None => return,
+    };
+
+    for (path, instances) in outdated {
+        // Instances are kept as is, if the scene can't be loaded
+        let instances = match Scene::load(&path) {
+            Ok(scene) => {
+                log::info!("Scene '{}' is changed, respawning", path.display());
+                let scene = scene.prepare(&mut asset_manager);
+
+                instances.into_iter()
+                    .map(|entities| {
+                        for entity in entities {
+                            world.despawn(entity).ok();
+                        }
+
+                        world.spawn_instance(&scene, None)
+                    })
+                    .collect()
+            },
+            Err(error) => {
+                log::error!("Cannot reload scene '{}': {error}", path.display());
+                instances
+            },
+        };
+
+        if let Some(watcher) = &mut asset_manager.watcher {
+            for entities in instances {
+                watcher.watch_scene(path.clone(), entities);
+            }
+        }
+    }
+}
+
+/// Re-apply bases of the prefabs to their spawned instances, when the prefab or its bases
+/// are changed. It's added by [`HotReload`] extension
+pub fn hot_reload_prefabs(
+    mut world: Write<World>,
+    mut asset_manager: Write<AssetManager>,
+    events: Read<Events>,
+){
+    let prefabs = match asset_manager.assets::<Prefab>() {
+        Some(prefabs) => prefabs,
+        None => return,
+    };
+
+    let mut reloaded = HashSet::new();
+    let mut paths = vec![];
+
+    if let Some(handler) = events.get_handler::<AssetEvent>() {
+        for event in handler.iter() {
+            match event {
+                AssetEvent::Modified(id) if id.asset_type == Prefab::TYPE => {
+                    let handle = Handle::<Prefab>::from_index(id.index);
+                    reloaded.insert(handle);
+                    paths.extend(prefabs.path_of(handle).map(Path::to_path_buf));
+                },
+                AssetEvent::FileModified(path) => paths.push(path.clone()),
+                _ => {},
+            }
+        }
+    }
+
+    if paths.is_empty() && reloaded.is_empty() {
+        return;
+    }
+
+    let outdated = world.query_mut::<&PrefabInstance>()
+        .into_iter()
+        .map(|(_, instance)| instance.prefab)
+        .filter(|&handle| reloaded.contains(&handle) || prefabs.get(handle)
+            .is_some_and(|prefab| paths.iter().any(|path| prefab.depends_on(path))))
+        .collect::<HashSet<_>>();
+
+    for handle in outdated {
+        log::info!("Base of prefab {handle:?} is changed, re-applying it to the instances");
+
+        if let Err(error) = Prefab::reapply(handle, &mut world, &mut asset_manager) {
+            log::error!("Cannot re-apply prefab {handle:?}: {error}");
+        }
+    }
+}
+
 #[cfg(feature = "render")]
 pub fn generate_textures(
     mut asset_manager: Write<AssetManager>,
     mut renderer: Write<Renderer>,
 ) -> FlatboxResult<()> {
-    for texture in &mut asset_manager.textures {
+    let asset_manager = &mut *asset_manager;
+
+    if !asset_manager.outdated_textures.is_empty() {
+        renderer.idle()?;
+
+        for index in asset_manager.outdated_textures.drain(..) {
+            if let Some(texture) = asset_manager.textures.get_mut(index) {
+                texture.cleanup(&mut renderer);
+            }
+        }
+    }
+
+    for (index, texture) in asset_manager.textures.iter_mut().enumerate() {
+        if asset_manager.loader.state(AssetId::new('T', index)) == Some(&LoadState::Loading) {
+            continue;
+        }
+
         if texture.vk_image.is_none() {
             texture.generate(&mut renderer)?;
         }
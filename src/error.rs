@@ -1,6 +1,7 @@
 use thiserror::Error;
 use crate::physics::error::PhysicsError;
 use crate::audio::error::AudioError;
+use crate::assets::save_load::SaveError;
 #[cfg(feature = "gltf")]
 use crate::render::pbr::gltf::GltfError;
 
@@ -44,9 +45,21 @@ pub enum Result {
     #[error("Physics error")]
     PhysicsError(#[from] PhysicsError),
     
+    /// Error during reading save archive. It's often caused by corrupted or too new save
+    #[error("Save error")]
+    SaveError(#[from] SaveError),
+    
     /// Extended RON error
     #[error("RON error")]
     RonError(#[from] ron::Error),
+
+    /// Error during binary serialization
+    #[error("Binary serialization error")]
+    BinarySerializationError(#[from] rmp_serde::encode::Error),
+
+    /// Error during binary deserialization
+    #[error("Binary deserialization error")]
+    BinaryDeserializationError(#[from] rmp_serde::decode::Error),
     
     /// Custom error type. Use when other error types don't fit
     #[error("Error happened: {0}")]
@@ -86,6 +86,7 @@ compile_error!("Feature \"render\" must be enabled in order to use \"gltf\"!");
 use std::any::TypeId;
 
 use crate::scripting::*;
+use crate::audio::{AudioBackend, AudioManager, Beat, SoundFinished, DEFAULT_CLOCK_CAPACITY};
 use crate::assets::*;
 use crate::ecs::*;
 use crate::physics::*;
@@ -161,7 +162,7 @@ impl Flatbox {
         if window_builder.init_logger {
             init_logger();
         }
-        
+
         Flatbox {
             world: World::new(),
             lua_manager: LuaManager::new(),
@@ -173,7 +174,14 @@ impl Flatbox {
             events: Events::new(),
             physics_handler: PhysicsHandler::new(),
             time_handler: Time::new(),
-            asset_manager: AssetManager::new(window_builder.cast_count, window_builder.listener_count),
+            asset_manager: AssetManager::with_audio(
+                AudioManager::with_clock_capacity(
+                    window_builder.cast_count,
+                    window_builder.listener_count,
+                    window_builder.audio_backend,
+                    window_builder.clock_capacity,
+                ).expect("Cannot create audio manager"),
+            ),
             extensions: vec![],
             window_builder: window_builder.clone(),
             #[cfg(feature = "render")]
@@ -219,6 +227,7 @@ impl Flatbox {
         self.schedules.get_mut("update").unwrap()
             .add_system(time_system)
             .add_system(update_physics)
+            .add_system(update_assets)
             .add_system(processing_audio);
             
         #[cfg(feature = "render")]
@@ -227,6 +236,12 @@ impl Flatbox {
             .add_system(rendering_system)
             .add_system(update_lights)
             .add_system(generate_textures);
+
+        self.add_events::<AssetEvent>();
+        self.add_events::<SoundFinished>();
+        self.add_events::<Beat>();
+        self.add_events::<Collision>();
+        self.add_events::<ContactForce>();
             
         self
     }
@@ -317,6 +332,10 @@ pub struct WindowBuilder {
     pub listener_count: usize,
     /// Maximum count of audio casts
     pub cast_count: usize,
+    /// Audio output. Default is the system device with fallback to the silent mock backend
+    pub audio_backend: AudioBackend,
+    /// Maximum count of tempo clocks
+    pub clock_capacity: usize,
 
     // === RENDERING SETTINGS ===
     /// Window clear background color:
@@ -345,6 +364,8 @@ impl Default for WindowBuilder {
             init_logger: true, 
             listener_count: 8, 
             cast_count: 128, 
+            audio_backend: AudioBackend::Cpal,
+            clock_capacity: DEFAULT_CLOCK_CAPACITY,
             #[cfg(feature = "render")]
             clear_color: nalgebra::Vector3::new(0.0, 0.0, 0.0), 
             #[cfg(feature = "render")]
@@ -72,6 +72,15 @@ impl Transform {
         }
     }
     
+    /// Convert transform, which is local to this one, to the global space
+    pub fn combine(&self, local: &Transform) -> Transform {
+        Transform {
+            translation: self.translation + self.rotation * self.scale.vector.component_mul(&local.translation),
+            rotation: self.rotation * local.rotation,
+            scale: Scale3::from(self.scale.vector.component_mul(&local.scale.vector)),
+        }
+    }
+    
     pub fn local_x(&self) -> Unit<Vector3<f32>> {
         let m = self.to_matrices().0;
         
@@ -0,0 +1,128 @@
+use std::collections::HashMap;
+
+use nalgebra::Vector3;
+use rapier3d::{
+    crossbeam::channel::{unbounded, Receiver},
+    prelude::{
+        ChannelEventCollector,
+        ColliderHandle,
+        ColliderSet,
+        CollisionEvent as RapierCollisionEvent,
+        CollisionEventFlags,
+        ContactForceEvent,
+    },
+};
+
+use crate::ecs::Entity;
+
+/// Event, which is sent by `update_physics` when colliders of two entities start or stop touching.
+/// Colliders must have `ActiveEvents::COLLISION_EVENTS` set. Can be read with `Events::get_handler::<Collision>()`
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Collision {
+    Started(Entity, Entity),
+    /// Collision has stopped. `removed` is set, when one of the colliders has been removed
+    Stopped { entities: (Entity, Entity), removed: bool },
+    /// Entity has entered the sensor collider of another one
+    SensorEntered(Entity, Entity),
+    SensorExited { entities: (Entity, Entity), removed: bool },
+}
+
+impl Collision {
+    pub fn entities(&self) -> (Entity, Entity) {
+        match *self {
+            Collision::Started(e1, e2) | Collision::SensorEntered(e1, e2) => (e1, e2),
+            Collision::Stopped { entities, .. } | Collision::SensorExited { entities, .. } => entities,
+        }
+    }
+
+    /// Check whether the `entity` takes part in the collision
+    pub fn involves(&self, entity: Entity) -> bool {
+        let (e1, e2) = self.entities();
+        e1 == entity || e2 == entity
+    }
+}
+
+/// Event about forces between colliders of two entities, which is sent by `update_physics`.
+/// Colliders must have `ActiveEvents::CONTACT_FORCE_EVENTS` set
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ContactForce {
+    pub entities: (Entity, Entity),
+    /// Sum of all the forces between the colliders
+    pub total_force: Vector3<f32>,
+    /// Sum of the magnitudes of all the forces
+    pub total_force_magnitude: f32,
+    pub max_force_direction: Vector3<f32>,
+    pub max_force_magnitude: f32,
+}
+
+/// Channels, which rapier sends events of the simulation step to
+pub(crate) struct PhysicsEventChannels {
+    pub(crate) collector: ChannelEventCollector,
+    collisions: Receiver<RapierCollisionEvent>,
+    contact_forces: Receiver<ContactForceEvent>,
+    /// Entities of the colliders. They are kept until the events about collider
+    /// removal are sent, even if the entities have been despawned
+    entities: HashMap<ColliderHandle, Entity>,
+}
+
+impl PhysicsEventChannels {
+    /// Remember the entity of the collider
+    pub(crate) fn insert(&mut self, collider: ColliderHandle, entity: Entity) {
+        self.entities.insert(collider, entity);
+    }
+
+    /// Receive events of the last steps and translate colliders to entities.
+    /// Events of the colliders without entities are dropped. Entities of the
+    /// removed colliders are forgotten afterwards
+    pub(crate) fn drain(&mut self, colliders: &ColliderSet) -> (Vec<Collision>, Vec<ContactForce>) {
+        let entities = &self.entities;
+        let pair = |c1, c2| Some((*entities.get(&c1)?, *entities.get(&c2)?));
+
+        let collisions = self.collisions.try_iter().filter_map(|event| {
+            let (e1, e2) = pair(event.collider1(), event.collider2())?;
+
+            Some(match event {
+                RapierCollisionEvent::Started(_, _, flags) if flags.contains(CollisionEventFlags::SENSOR) => {
+                    Collision::SensorEntered(e1, e2)
+                },
+                RapierCollisionEvent::Started(..) => Collision::Started(e1, e2),
+                RapierCollisionEvent::Stopped(_, _, flags) => {
+                    let removed = flags.contains(CollisionEventFlags::REMOVED);
+
+                    match flags.contains(CollisionEventFlags::SENSOR) {
+                        true => Collision::SensorExited { entities: (e1, e2), removed },
+                        false => Collision::Stopped { entities: (e1, e2), removed },
+                    }
+                },
+            })
+        }).collect();
+
+        let contact_forces = self.contact_forces.try_iter().filter_map(|event| {
+            Some(ContactForce {
+                entities: pair(event.collider1, event.collider2)?,
+                total_force: event.total_force,
+                total_force_magnitude: event.total_force_magnitude,
+                max_force_direction: event.max_force_direction,
+                max_force_magnitude: event.max_force_magnitude,
+            })
+        }).collect();
+
+        self.entities.retain(|collider, _| colliders.contains(*collider));
+
+        (collisions, contact_forces)
+    }
+}
+
+impl Default for PhysicsEventChannels {
+    fn default() -> Self {
+        let (collision_sender, collisions) = unbounded();
+        let (contact_force_sender, contact_forces) = unbounded();
+
+        PhysicsEventChannels {
+            collector: ChannelEventCollector::new(collision_sender, contact_force_sender),
+            collisions,
+            contact_forces,
+            entities: HashMap::new(),
+        }
+    }
+}
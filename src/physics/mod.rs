@@ -1,4 +1,5 @@
 pub mod components;
+pub mod events;
 pub mod physics_handler;
 #[cfg(feature = "render")]
 pub mod debug_render;
@@ -7,6 +8,7 @@ pub mod mesh;
 pub mod error;
 
 pub use components::*;
+pub use events::*;
 pub use physics_handler::*;
 #[cfg(feature = "render")]
 pub use debug_render::*;
@@ -15,8 +17,10 @@ pub use mesh::*;
 pub use error::*;
 
 pub use rapier3d::prelude::{
+    ActiveEvents,
     RigidBodyBuilder,
     ColliderBuilder,
     RigidBodyHandle,
     ColliderHandle,
+    QueryFilter,
 };
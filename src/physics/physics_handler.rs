@@ -11,6 +11,7 @@ use crate::error::FlatboxResult;
 use super::{
     error::PhysicsError,
     components::BodyHandle,
+    events::{PhysicsEventChannels, Collision, ContactForce},
 };
 
 /// Collection for physics simulations
@@ -25,6 +26,8 @@ pub struct PhysicsHandler {
 
     #[serde(skip_serializing, skip_deserializing)]
     pub physics_pipeline: PhysicsPipeline,
+    #[serde(skip_serializing, skip_deserializing)]
+    pub query_pipeline: QueryPipeline,
     
     pub gravity: Vector3<f32>,
     pub integration_parameters: IntegrationParameters,
@@ -35,7 +38,10 @@ pub struct PhysicsHandler {
     pub multibody_joint_set: MultibodyJointSet,
     pub ccd_solver: CCDSolver,
     pub physics_hooks: (),
-    pub event_handler: (),
+    /// Collision and contact force events. They replace the former public `event_handler`
+    /// field and are sent to [`Events`](crate::ecs::Events) by `update_physics`
+    #[serde(skip_serializing, skip_deserializing)]
+    pub(crate) events: PhysicsEventChannels,
 }
 
 impl PhysicsHandler {
@@ -144,8 +150,46 @@ impl PhysicsHandler {
             &mut self.multibody_joint_set,
             &mut self.ccd_solver,
             &self.physics_hooks,
-            &self.event_handler,
-        )
+            &self.events.collector,
+        );
+
+        self.query_pipeline.update(&self.island_manager, &self.rigidbody_set, &self.collider_set);
+    }
+
+    /// Receive events of the last steps. See [`PhysicsEventChannels::drain`]
+    pub(crate) fn drain_events(&mut self) -> (Vec<Collision>, Vec<ContactForce>) {
+        self.events.drain(&self.collider_set)
+    }
+
+    /// Colliders, which pass the `filter` and the ray hits, along with the distances to the hits.
+    /// Every collider is hit once
+    pub fn intersections_with_ray(
+        &self,
+        origin: Vector3<f32>,
+        direction: Vector3<f32>,
+        max_distance: f32,
+        filter: QueryFilter,
+    ) -> Vec<(ColliderHandle, f32)> {
+        let mut hits = vec![];
+
+        let Some(direction) = direction.try_normalize(f32::EPSILON) else {
+            return hits;
+        };
+
+        self.query_pipeline.intersections_with_ray(
+            &self.rigidbody_set,
+            &self.collider_set,
+            &Ray::new(origin.into(), direction),
+            max_distance,
+            true,
+            filter,
+            |handle, intersection| {
+                hits.push((handle, intersection.toi));
+                true
+            },
+        );
+
+        hits
     }
 }
 
@@ -161,6 +205,7 @@ impl Default for PhysicsHandler {
                 DebugRenderMode::COLLIDER_SHAPES,
             ),
             physics_pipeline: PhysicsPipeline::new(),
+            query_pipeline: QueryPipeline::new(),
             
             gravity: vector![0.0, -2.0, 0.0],
             integration_parameters: IntegrationParameters::default(),
@@ -171,7 +216,7 @@ impl Default for PhysicsHandler {
             multibody_joint_set: MultibodyJointSet::new(),
             ccd_solver: CCDSolver::new(),
             physics_hooks: (),
-            event_handler: (),
+            events: PhysicsEventChannels::default(),
         }
     }
 }
@@ -14,7 +14,7 @@ pub use ui::{GuiContext, GuiEvent, GuiHandler, Key, gui};
 
 pub use pbr::camera::{Camera, CameraBuilder, CameraBundle, CameraType};
 pub use pbr::model::{Mesh, MeshType, Model, ModelBundle, ModelBundleBuilder};
-pub use pbr::texture::{Texture, TextureLoadType, TextureType, Filter};
+pub use pbr::texture::{Texture, TextureLoadType, TextureType, Filter, ColorSpace, CubemapLayout};
 pub use pbr::light::{DirectionalLight, PointLight};
 pub use pbr::material::{Material, DefaultMat, DefaultMatBuilder, CachedMaterials};
 pub use pbr::color::Color;
@@ -21,7 +21,7 @@ use gltf::{
 };
 
 use crate::error::FlatboxResult;
-use crate::assets::asset_manager::AssetManager;
+use crate::assets::{asset_manager::AssetManager, source::Vfs};
 use crate::{
     render::pbr::{
         model::{Mesh, ModelBundle, Vertex},
@@ -49,7 +49,7 @@ pub fn load_gltf<P: AsRef<Path>>(
     path: P,
     assets: &mut AssetManager,
 ) -> FlatboxResult<Vec<ModelBundle>> {
-    let scene = Gltf::open(path).map_err(|e| GltfError::from(e))?.document;
+    let scene = Gltf::from_slice(&Vfs::read(path)?).map_err(|e| GltfError::from(e))?.document;
 
     let mut materials = vec![];
 
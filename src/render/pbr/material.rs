@@ -4,7 +4,7 @@ use as_any::AsAny;
 use parking_lot::RwLock;
 use vk_shader_macros::include_glsl;
 
-use crate::assets::AssetHandle;
+use crate::assets::{AssetHandle, AssetMapping};
 use crate::render::backend::shader::*;
 
 pub use flatbox_macros::Material;
@@ -23,6 +23,9 @@ pub trait Material: AsAny + std::fmt::Debug + Send + Sync {
     fn input() -> ShaderInput
     where 
         Self: Sized;
+
+    /// Remap texture handles, when the material is merged into another [`AssetManager`](crate::assets::AssetManager)
+    fn map_assets(&mut self, _mapping: &AssetMapping) {}
 }
 
 /// Default material, which uses standard shader and graphics pipeline
@@ -152,6 +155,14 @@ impl Material for DefaultMat {
             topology: ShaderTopology::TRIANGLE_LIST,
         }
     }
+
+    fn map_assets(&mut self, mapping: &AssetMapping) {
+        self.albedo = mapping.map_texture_index(self.albedo);
+        self.metallic_map = mapping.map_texture_index(self.metallic_map);
+        self.roughness_map = mapping.map_texture_index(self.roughness_map);
+        self.normal_map = mapping.map_texture_index(self.normal_map);
+        self.ao_map = mapping.map_texture_index(self.ao_map);
+    }
 }
 
 pub struct DefaultMatBuilder {
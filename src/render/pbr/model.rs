@@ -1,6 +1,7 @@
 use std::fmt::{self, Debug};
 use std::path::{Path, PathBuf};
 use std::collections::HashMap;
+use std::io::Cursor;
 use serde::{
     Serialize, 
     Deserialize,
@@ -17,7 +18,7 @@ use crate::{
     error::FlatboxResult,
 };
 
-use crate::assets::AssetHandle;
+use crate::assets::{AssetHandle, Vfs};
 use crate::ecs::*;
 use crate::math::transform::Transform;
 
@@ -327,15 +328,31 @@ impl Mesh {
     where 
         P: AsRef<Path> + Debug
     {
-        let (models, _) = tobj::load_obj(
-            path,
+        Mesh::try_load_obj(path).expect("Cannot load OBJ file")
+    }
+
+    /// Load meshes from OBJ file without panicking on invalid files
+    pub fn try_load_obj<P>(path: P) -> FlatboxResult<Vec<Self>>
+    where 
+        P: AsRef<Path> + Debug
+    {
+        let bytes = Vfs::read(path.as_ref())?;
+        let directory = path.as_ref().parent().unwrap_or(Path::new("")).to_owned();
+
+        let (models, _) = tobj::load_obj_buf(
+            &mut Cursor::new(bytes),
             &tobj::LoadOptions {
                 single_index: true,
                 triangulate: true,
                 ignore_points: true,
                 ..Default::default()
             },
-        ).expect("Cannot load OBJ file");
+            |mtl_path| {
+                let bytes = Vfs::read(directory.join(mtl_path))
+                    .map_err(|_| tobj::LoadError::OpenFileFailed)?;
+                tobj::load_mtl_buf(&mut Cursor::new(bytes))
+            },
+        ).map_err(|e| crate::Result::yell(format!("Cannot load OBJ file '{}': {e}", path.as_ref().display())))?;
         
         let mut meshes = Vec::<Mesh>::new();
         
@@ -384,7 +401,7 @@ impl Mesh {
             });
         }
         
-        return meshes;
+        Ok(meshes)
     }
     
     /// Subdivide mesh triangles
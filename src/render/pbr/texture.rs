@@ -21,6 +21,7 @@ use crate::render::{
 };
 
 use crate::error::FlatboxResult;
+use crate::assets::{Vfs, AssetMeta, TextureMeta};
 
 #[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
 pub enum Filter {
@@ -77,6 +78,67 @@ pub enum TextureType {
     Cubemap
 }
 
+/// Color space of the texture data
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ColorSpace {
+    /// Color textures (albedo, UI etc.)
+    #[default]
+    Srgb,
+    /// Non-color data (normal, roughness, metallic maps etc.)
+    Linear,
+}
+
+impl From<ColorSpace> for vk::Format {
+    fn from(color_space: ColorSpace) -> Self {
+        match color_space {
+            ColorSpace::Srgb => vk::Format::R8G8B8A8_SRGB,
+            ColorSpace::Linear => vk::Format::R8G8B8A8_UNORM,
+        }
+    }
+}
+
+/// Arrangement of the cubemap faces (+X, -X, +Y, -Y, +Z, -Z) in the image
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CubemapLayout {
+    /// Faces are placed one under another
+    #[default]
+    VerticalStrip,
+    /// Faces are placed in a row
+    HorizontalStrip,
+    /// Horizontal cross of 4x3 faces:
+    /// ```text
+    ///      +Y
+    /// -X   +Z   +X   -Z
+    ///      -Y
+    /// ```
+    HorizontalCross,
+}
+
+impl CubemapLayout {
+    /// Rearrange faces of the image to the vertical strip, which is used by the renderer
+    pub fn to_vertical_strip(&self, image: RgbaImage) -> RgbaImage {
+        // Face positions in the image in face size units
+        let positions: [(u32, u32); 6] = match self {
+            CubemapLayout::VerticalStrip => return image,
+            CubemapLayout::HorizontalStrip => [(0, 0), (1, 0), (2, 0), (3, 0), (4, 0), (5, 0)],
+            CubemapLayout::HorizontalCross => [(2, 1), (0, 1), (1, 0), (1, 2), (1, 1), (3, 1)],
+        };
+
+        let size = match self {
+            CubemapLayout::HorizontalCross => image.width() / 4,
+            _ => image.height(),
+        };
+
+        let mut strip = RgbaImage::new(size, size * 6);
+        for (face, (x, y)) in positions.iter().enumerate() {
+            let view = image::imageops::crop_imm(&image, x * size, y * size, size, size);
+            image::imageops::replace(&mut strip, &*view, 0, (face as u32 * size) as i64);
+        }
+
+        strip
+    }
+}
+
 #[readonly::make]
 pub struct Texture {
     /// Texture load type. It can be selected manually and is
@@ -90,6 +152,10 @@ pub struct Texture {
     /// Image processing filter. In most cases you need `Linear` 
     /// for smooth textures and `Nearest` for pixelized
     pub filter: Filter,
+    /// Whether mipmaps are generated on uploading to GPU
+    pub generate_mipmaps: bool,
+    /// Color space of the image data
+    pub color_space: ColorSpace,
     /// Raw image data
     pub(crate) image: Option<RgbaImage>,
     /// Vulkan image data
@@ -108,6 +174,8 @@ impl Clone for Texture {
             texture_load_type: self.texture_load_type.clone(), 
             texture_type: self.texture_type.clone(), 
             filter: self.filter.clone(), 
+            generate_mipmaps: self.generate_mipmaps,
+            color_space: self.color_space,
             image: self.image.clone(), 
             vk_image: None, 
             image_allocation: None, 
@@ -204,10 +272,14 @@ impl<'de> Deserialize<'de> for Texture {
                     },
                 };
 
+                let (generate_mipmaps, color_space) = Texture::import_settings(&texture_load_type);
+
                 Ok(Texture {
                     texture_load_type,
                     texture_type,
                     filter,
+                    generate_mipmaps,
+                    color_space,
                     image: Some(raw_image),
                     vk_image: None,
                     image_allocation: None,
@@ -276,10 +348,14 @@ impl<'de> Deserialize<'de> for Texture {
                     },
                 };
 
+                let (generate_mipmaps, color_space) = Texture::import_settings(&texture_load_type);
+
                 Ok(Texture {
                     texture_load_type,
                     texture_type,
                     filter,
+                    generate_mipmaps,
+                    color_space,
                     image: Some(raw_image),
                     vk_image: None,
                     image_allocation: None,
@@ -310,6 +386,8 @@ impl Texture {
             texture_load_type: TextureLoadType::Loaded(path.into()),
             texture_type,
             filter,
+            generate_mipmaps: false,
+            color_space: ColorSpace::Srgb,
             image: None,
             vk_image: None,
             image_allocation: None,
@@ -318,6 +396,17 @@ impl Texture {
         }
     }
 
+    /// Create empty texture with import settings from the asset meta
+    pub fn new_from_meta(
+        path: &str,
+        meta: &TextureMeta,
+    ) -> Self {
+        let mut texture = Texture::new_from_path(path, meta.filter, meta.texture_type.clone());
+        texture.generate_mipmaps = meta.generate_mipmaps;
+        texture.color_space = meta.color_space;
+        texture
+    }
+
     pub fn new_from_raw(
         raw_data: &[u8],
         filter: Filter,
@@ -329,6 +418,8 @@ impl Texture {
             texture_load_type: TextureLoadType::Generic,
             texture_type,
             filter, 
+            generate_mipmaps: false,
+            color_space: ColorSpace::Srgb,
             image: Some(RgbaImage::from_raw(width, height, raw_data.into())
                 .unwrap_or(Texture::no_image_internal())), 
             vk_image: None, 
@@ -351,6 +442,8 @@ impl Texture {
             texture_load_type: TextureLoadType::Color(color, width, height),
             texture_type,
             filter: Filter::Nearest, 
+            generate_mipmaps: false,
+            color_space: ColorSpace::Srgb,
             image, 
             vk_image: None, 
             image_allocation: None, 
@@ -381,6 +474,8 @@ impl Texture {
             texture_load_type: TextureLoadType::Generic,
             texture_type: TextureType::Plain,
             filter: Filter::Nearest,
+            generate_mipmaps: false,
+            color_space: ColorSpace::Srgb,
             image: Some(Texture::no_image_internal()),
             vk_image: None,
             image_allocation: None,
@@ -394,6 +489,8 @@ impl Texture {
             texture_load_type: TextureLoadType::Generic,
             texture_type: TextureType::Plain,
             filter: Filter::Linear,
+            generate_mipmaps: false,
+            color_space: ColorSpace::Srgb,
             image: Some(Texture::no_image_internal()),
             vk_image: None,
             image_allocation: None,
@@ -450,6 +547,16 @@ impl Texture {
             (image.width(), image.width())
         };
 
+        let format: vk::Format = self.color_space.into();
+        let mip_levels = match self.generate_mipmaps {
+            true => 32 - width.max(height).leading_zeros(),
+            false => 1,
+        };
+        let layer_count: u32 = match is_cubemap {
+            true => 6,
+            false => 1,
+        };
+
         unsafe { renderer.device.device_wait_idle()?; }
         
         let mut img_create_info = vk::ImageCreateInfo::builder()
@@ -459,11 +566,12 @@ impl Texture {
                 height,
                 depth: 1,
             })
-            .mip_levels(1)
-            .format(vk::Format::R8G8B8A8_SRGB)
+            .mip_levels(mip_levels)
+            .format(format)
             .samples(vk::SampleCountFlags::TYPE_1)
             .array_layers(1)
             .usage(
+                vk::ImageUsageFlags::TRANSFER_SRC |
                 vk::ImageUsageFlags::TRANSFER_DST |
                 vk::ImageUsageFlags::SAMPLED
             );
@@ -495,10 +603,10 @@ impl Texture {
         let mut view_create_info = vk::ImageViewCreateInfo::builder()
             .image(vk_image)
             .view_type(vk::ImageViewType::TYPE_2D)
-            .format(vk::Format::R8G8B8A8_SRGB)
+            .format(format)
             .subresource_range(vk::ImageSubresourceRange {
                 aspect_mask: vk::ImageAspectFlags::COLOR,
-                level_count: 1,
+                level_count: mip_levels,
                 layer_count: 1,
                 ..Default::default()
             });
@@ -513,7 +621,9 @@ impl Texture {
         // Create Sampler
         let sampler_info = vk::SamplerCreateInfo::builder()
             .mag_filter(raw_filter)
-            .min_filter(raw_filter);
+            .min_filter(raw_filter)
+            .mipmap_mode(vk::SamplerMipmapMode::LINEAR)
+            .max_lod(mip_levels as f32);
         let sampler = unsafe { renderer.device.create_sampler(&sampler_info, None)? };
         
         // Prepare buffer for the texture
@@ -555,7 +665,7 @@ impl Texture {
             .subresource_range(vk::ImageSubresourceRange {
                 aspect_mask: vk::ImageAspectFlags::COLOR,
                 base_mip_level: 0,
-                level_count: 1,
+                level_count: mip_levels,
                 base_array_layer: 0,
                 layer_count: 1,
             })
@@ -620,11 +730,98 @@ impl Texture {
             );
         }
         
-        // Change image layout for fragment shader
+        // Generate mipmaps, moving every source level to the shader layout
+        let (mut mip_width, mut mip_height) = (width as i32, height as i32);
+
+        for level in 1..mip_levels {
+            let mut barrier = vk::ImageMemoryBarrier::builder()
+                .image(vk_image)
+                .src_access_mask(vk::AccessFlags::TRANSFER_WRITE)
+                .dst_access_mask(vk::AccessFlags::TRANSFER_READ)
+                .old_layout(vk::ImageLayout::TRANSFER_DST_OPTIMAL)
+                .new_layout(vk::ImageLayout::TRANSFER_SRC_OPTIMAL)
+                .subresource_range(vk::ImageSubresourceRange {
+                    aspect_mask: vk::ImageAspectFlags::COLOR,
+                    base_mip_level: level - 1,
+                    level_count: 1,
+                    base_array_layer: 0,
+                    layer_count,
+                })
+                .build();
+
+            unsafe {
+                renderer.device.cmd_pipeline_barrier(
+                    copycmdbuffer,
+                    vk::PipelineStageFlags::TRANSFER,
+                    vk::PipelineStageFlags::TRANSFER,
+                    vk::DependencyFlags::empty(),
+                    &[],
+                    &[],
+                    &[barrier],
+                )
+            };
+
+            let (next_width, next_height) = ((mip_width / 2).max(1), (mip_height / 2).max(1));
+            let blit = vk::ImageBlit {
+                src_subresource: vk::ImageSubresourceLayers {
+                    aspect_mask: vk::ImageAspectFlags::COLOR,
+                    mip_level: level - 1,
+                    base_array_layer: 0,
+                    layer_count,
+                },
+                src_offsets: [
+                    vk::Offset3D { x: 0, y: 0, z: 0 },
+                    vk::Offset3D { x: mip_width, y: mip_height, z: 1 },
+                ],
+                dst_subresource: vk::ImageSubresourceLayers {
+                    aspect_mask: vk::ImageAspectFlags::COLOR,
+                    mip_level: level,
+                    base_array_layer: 0,
+                    layer_count,
+                },
+                dst_offsets: [
+                    vk::Offset3D { x: 0, y: 0, z: 0 },
+                    vk::Offset3D { x: next_width, y: next_height, z: 1 },
+                ],
+            };
+
+            unsafe {
+                renderer.device.cmd_blit_image(
+                    copycmdbuffer,
+                    vk_image,
+                    vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+                    vk_image,
+                    vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+                    &[blit],
+                    vk::Filter::LINEAR,
+                )
+            };
+
+            barrier.old_layout = vk::ImageLayout::TRANSFER_SRC_OPTIMAL;
+            barrier.new_layout = vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL;
+            barrier.src_access_mask = vk::AccessFlags::TRANSFER_READ;
+            barrier.dst_access_mask = vk::AccessFlags::SHADER_READ;
+
+            unsafe {
+                renderer.device.cmd_pipeline_barrier(
+                    copycmdbuffer,
+                    vk::PipelineStageFlags::TRANSFER,
+                    vk::PipelineStageFlags::FRAGMENT_SHADER,
+                    vk::DependencyFlags::empty(),
+                    &[],
+                    &[],
+                    &[barrier],
+                )
+            };
+
+            (mip_width, mip_height) = (next_width, next_height);
+        }
+
+        // Change image layout of the last mip level for fragment shader
 
         let mut subresource_range = vk::ImageSubresourceRange {
             aspect_mask: vk::ImageAspectFlags::COLOR,
-            base_mip_level: 0,
+            base_mip_level: mip_levels - 1,
             level_count: 1,
             base_array_layer: 0,
             layer_count: 1,
@@ -705,12 +902,38 @@ impl Texture {
     fn create_from_path(
         path: impl AsRef<Path>,
     ) -> RgbaImage {
-        image::open(path.as_ref().clone())
+        Texture::decode_file(path).unwrap_or_else(|error| {
+            log::error!("{error}");
+            Texture::no_image_internal()
+        })
+    }
+
+    /// Read image through the [`Vfs`] and decode it. Cubemap faces are arranged according to the asset meta
+    pub(crate) fn decode_file(path: impl AsRef<Path>) -> Result<RgbaImage, String> {
+        let path = path.as_ref();
+        let bytes = Vfs::read(path)
+            .map_err(|e| format!("Error loading texture '{}': {e}", path.display()))?;
+
+        let image = image::load_from_memory(&bytes)
             .map(|img| img.to_rgba8())
-            .unwrap_or_else(|_|{
-                log::error!("Error loading texture: path '{}' not found", path.as_ref().display());
-                Texture::no_image_internal()
-            })
+            .map_err(|e| format!("Error loading texture '{}': {e}", path.display()))?;
+
+        match TextureMeta::load(path) {
+            Some(meta) if meta.texture_type == TextureType::Cubemap => {
+                Ok(meta.cubemap_layout.to_vertical_strip(image))
+            },
+            _ => Ok(image),
+        }
+    }
+
+    /// Get mipmap and color space settings of the texture from the asset meta
+    fn import_settings(texture_load_type: &TextureLoadType) -> (bool, ColorSpace) {
+        match texture_load_type {
+            TextureLoadType::Loaded(path) => TextureMeta::load(path)
+                .map(|meta| (meta.generate_mipmaps, meta.color_space))
+                .unwrap_or((false, ColorSpace::Srgb)),
+            _ => (false, ColorSpace::Srgb),
+        }
     }
 
     fn no_image_internal() -> RgbaImage {
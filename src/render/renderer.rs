@@ -399,15 +399,17 @@ fn update_texture_sets(
     device: &ash::Device,
 ){
     let textures_image_infos = asset_manager.descriptor_image_info();
-    let textures_descriptor_write_image = vk::WriteDescriptorSet::builder()
-        .dst_set(descriptor_pool.texture_sets[swapchain.current_image])
-        .dst_binding(0)
-        .dst_array_element(0)
-        .descriptor_type(vk::DescriptorType::COMBINED_IMAGE_SAMPLER)
-        .image_info(&textures_image_infos)
-        .build();
-
-    unsafe { device.update_descriptor_sets(&[textures_descriptor_write_image], &[]); }
+    if !textures_image_infos.is_empty() {
+        let textures_descriptor_write_image = vk::WriteDescriptorSet::builder()
+            .dst_set(descriptor_pool.texture_sets[swapchain.current_image])
+            .dst_binding(0)
+            .dst_array_element(0)
+            .descriptor_type(vk::DescriptorType::COMBINED_IMAGE_SAMPLER)
+            .image_info(&textures_image_infos)
+            .build();
+
+        unsafe { device.update_descriptor_sets(&[textures_descriptor_write_image], &[]); }
+    }
 
     if let Some(skybox) = &asset_manager.skybox {
         if let Some(skybox_image_info) = skybox.descriptor_image_info() {
@@ -579,6 +581,7 @@ fn render_egui(
 ){
     egui.context().set_visuals(egui::style::Visuals::dark());
     egui.begin_frame(&window.window.lock().unwrap());
+    event_handler.clear();
     event_handler.send(egui.context());
     let output = egui.end_frame(&mut window.window.lock().unwrap());
     let clipped_meshes = egui.context().tessellate(output.shapes);
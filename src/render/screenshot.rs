@@ -7,10 +7,19 @@ use crate::error::FlatboxResult;
 
 pub trait ScreenshotExt {
     fn screenshot(&mut self, path: &str) -> FlatboxResult<()>;
+
+    /// Capture current frame into the image
+    fn capture(&mut self) -> FlatboxResult<image::RgbaImage>;
 }
 
 impl ScreenshotExt for Renderer {
     fn screenshot(&mut self, path: &str) -> FlatboxResult<()> {
+        self.capture()?.save(path)?;
+        
+        Ok(())
+    }
+
+    fn capture(&mut self) -> FlatboxResult<image::RgbaImage> {
         // Create CommandBuffer
         let commandbuf_allocate_info = vk::CommandBufferAllocateInfo::builder()
             .command_pool(self.commandbuffer_pools.commandpool_graphics)
@@ -276,11 +285,8 @@ impl ScreenshotExt for Renderer {
             data,
         )
         .expect("Failed create ImageBuffer");
-        // Save image
-        let screen_image = image::DynamicImage::ImageRgba8(screen);
-        screen_image.save(path)?;        
         
-        Ok(())
+        Ok(screen)
     }
 }
 
@@ -5,6 +5,7 @@ use parking_lot::RwLock;
 use mlua::{Lua, UserData, ToLua};
 use parking_lot::{Mutex, MutexGuard};
 use super::script::Script;
+use crate::assets::Vfs;
 
 pub struct LuaManager {
     inner: Arc<Mutex<Lua>>
@@ -28,7 +29,12 @@ impl LuaManager {
     }
 
     pub fn execute(&self, script: &Script) -> Result<(), mlua::Error> {
-        self.inner().load(script.path.as_path()).exec()
+        let source = Vfs::read(&script.path).map_err(mlua::Error::external)?;
+
+        self.inner()
+            .load(&source)
+            .set_name(script.path.to_string_lossy())?
+            .exec()
     }
 
     fn inner(&self) -> MutexGuard<Lua> {